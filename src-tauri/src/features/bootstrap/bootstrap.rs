@@ -0,0 +1,257 @@
+use codex_bindings::debug;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::features::bootstrap::parse_spr;
+use crate::features::connection::{
+    create_codex_config_for_profile, get_storage_manager_with_handle,
+};
+use crate::features::settings::{
+    load_settings, save_settings, NodeProfile, DEFAULT_BOOTSTRAP_PRESET,
+};
+use crate::features::shared::{
+    BootstrapNodeStatus, BootstrapPreset, BootstrapReport, SprInfo, StorageError,
+};
+
+/// Event emitted once the reachability report after a node start is ready
+pub const BOOTSTRAP_REPORT_EVENT: &str = "bootstrap-report";
+
+// Time the node gets to contact its bootstrap nodes before the report is made
+const REPORT_DELAY: Duration = Duration::from_secs(15);
+
+static LAST_REPORT: Mutex<Option<BootstrapReport>> = Mutex::const_new(None);
+
+/// Bootstrap records of the public Codex testnet
+const TESTNET_NODES: &[&str] = &[
+    "spr:CiUIAhIhAiJvIcA_ZwPZ9ugVKDbmqwhJZaig5zKyLiuaicRcCGqLEgIDARo8CicAJQgCEiECIm8hwD9nA9n26BUoNuarCEllqKDnMrIuK5qJxFwIaosQ3d6esAYaCwoJBJ_f8zKRAnU6KkYwRAIgM0MvWNJL296kJ9gWvfatfmVvT-A7O2s8Mxp8l9c8EW0CIC-h-H-jBVSgFjg3Eny2u33qF7BDnWFzo7fGfZ7_qc9P",
+    "spr:CiUIAhIhAyUvcPkKoGE7-gh84RmKIPHJPdsX5Ugm_IHVJgF-Mmu_EgIDARo8CicAJQgCEiEDJS9w-QqgYTv6CHzhGYog8ck92xflSCb8gdUmAX4ya78QoemesAYaCwoJBES39Q2RAnVOKkYwRAIgLi3rouyaZFS_Uilx8k99ySdQCP1tsmLR21tDb9p8LcgCIG30o5YnEooQ1n6tgm9fCT7s53k6XlxyeSkD_uIO9mb3",
+    "spr:CiUIAhIhA6_j28xa--PvvOUxH10wKEm9feXEKJIK3Z9JQ5xXgSD9EgIDARo8CicAJQgCEiEDr-PbzFr74--85TEfXTAoSb195cQokgrdn0lDnFeBIP0QzOGesAYaCwoJBK6Kf1-RAnVEKkcwRQIhAPUH5nQrqG4OW86JQWphdSdnPA98ErQ0hL9OZH9a4e5kAiBBZmUl9KnhSOiDgU3_hvjXrXZXoMxhGuZ92_rk30sNDA",
+];
+
+/// Known networks. Records for further public networks belong here.
+pub fn bootstrap_presets() -> Vec<BootstrapPreset> {
+    vec![
+        BootstrapPreset {
+            name: DEFAULT_BOOTSTRAP_PRESET.to_string(),
+            description: "Bootstrap nodes built into the storage bindings".to_string(),
+            uses_builtin: true,
+            nodes: Vec::new(),
+        },
+        BootstrapPreset {
+            name: "testnet".to_string(),
+            description: "Bootstrap nodes of the public Codex testnet".to_string(),
+            uses_builtin: false,
+            nodes: TESTNET_NODES.iter().map(|spr| spr.to_string()).collect(),
+        },
+        BootstrapPreset {
+            name: "isolated".to_string(),
+            description: "Only the nodes added to this profile, for private networks".to_string(),
+            uses_builtin: false,
+            nodes: Vec::new(),
+        },
+    ]
+}
+
+fn find_preset(name: &str) -> Result<BootstrapPreset, StorageError> {
+    bootstrap_presets()
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| StorageError::Configuration(format!("Unknown bootstrap preset '{}'", name)))
+}
+
+/// Records the node should bootstrap from, `None` to keep the bindings' defaults
+pub fn bootstrap_nodes_for(profile: &NodeProfile) -> Option<Vec<String>> {
+    // LAN mode never reaches out to public networks, peers come from local discovery
+    if profile.lan_mode {
+        return Some(profile.bootstrap_nodes.clone());
+    }
+
+    let preset = find_preset(&profile.bootstrap_preset).unwrap_or_else(|_| {
+        warn!(
+            "Unknown bootstrap preset '{}', using the default",
+            profile.bootstrap_preset
+        );
+        bootstrap_presets().remove(0)
+    });
+
+    let mut nodes = preset.nodes;
+    for spr in &profile.bootstrap_nodes {
+        if !nodes.contains(spr) {
+            nodes.push(spr.clone());
+        }
+    }
+
+    if nodes.is_empty() && preset.uses_builtin {
+        None
+    } else {
+        Some(nodes)
+    }
+}
+
+/// Checks the preset and every bootstrap record of a profile before it is saved
+pub fn validate_bootstrap(profile: &NodeProfile) -> Result<(), StorageError> {
+    find_preset(&profile.bootstrap_preset)?;
+    for spr in &profile.bootstrap_nodes {
+        parse_spr(spr)?;
+    }
+    Ok(())
+}
+
+/// Restarts the node with the active profile's new bootstrap list and saves it.
+///
+/// Like a profile update this is refused while transfers run, and the list is only
+/// saved once the node runs with it.
+async fn update_bootstrap(
+    app_handle: &AppHandle,
+    update: impl FnOnce(&mut NodeProfile),
+) -> Result<NodeProfile, StorageError> {
+    let mut settings = load_settings(app_handle);
+    let active = settings.active_profile();
+    if !settings
+        .profiles
+        .iter()
+        .any(|profile| profile.name == active.name)
+    {
+        settings.profiles.push(active.clone());
+    }
+    let profile = settings
+        .profiles
+        .iter_mut()
+        .find(|profile| profile.name == active.name)
+        .expect("active profile is in the list");
+    update(profile);
+    validate_bootstrap(profile)?;
+    let profile = profile.clone();
+
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    manager
+        .switch_config(create_codex_config_for_profile(app_handle, &profile)?)
+        .await?;
+    save_settings(app_handle, &settings)?;
+    Ok(profile)
+}
+
+pub async fn add_bootstrap_node_with_handle(
+    spr: String,
+    app_handle: AppHandle,
+) -> Result<SprInfo, StorageError> {
+    let info = parse_spr(&spr)?;
+    update_bootstrap(&app_handle, |profile| {
+        if !profile.bootstrap_nodes.contains(&info.spr) {
+            profile.bootstrap_nodes.push(info.spr.clone());
+        }
+    })
+    .await?;
+    Ok(info)
+}
+
+pub async fn remove_bootstrap_node_with_handle(
+    spr: String,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    let spr = spr.trim().to_string();
+    update_bootstrap(&app_handle, |profile| {
+        profile.bootstrap_nodes.retain(|existing| *existing != spr)
+    })
+    .await
+}
+
+pub async fn set_bootstrap_preset_with_handle(
+    preset: String,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    find_preset(&preset)?;
+    update_bootstrap(&app_handle, |profile| profile.bootstrap_preset = preset).await
+}
+
+async fn build_report(app_handle: &AppHandle) -> Result<BootstrapReport, StorageError> {
+    let profile = load_settings(app_handle).active_profile();
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let node = manager.get_node().await?;
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+
+    let info = debug(&node)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let nodes = bootstrap_nodes_for(&profile)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|spr| {
+            // The table may hold a newer record of the same node, so match on the peer ID
+            let record = parse_spr(&spr).ok();
+            let reachable = record.as_ref().is_some_and(|record| {
+                info.table
+                    .nodes
+                    .iter()
+                    .any(|node| node.peer_id == record.peer_id && node.seen)
+            });
+            BootstrapNodeStatus {
+                addresses: record.map(|record| record.addresses).unwrap_or_default(),
+                spr,
+                reachable,
+            }
+        })
+        .collect();
+
+    Ok(BootstrapReport {
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        preset: profile.bootstrap_preset,
+        nodes,
+        dht_table_size: info.table.nodes.len(),
+    })
+}
+
+/// Reports which bootstrap nodes answered, a little while after the node started
+pub fn schedule_bootstrap_report(app_handle: AppHandle) {
+    tokio::spawn(async move {
+        tokio::time::sleep(REPORT_DELAY).await;
+
+        let report = match build_report(&app_handle).await {
+            Ok(report) => report,
+            Err(e) => {
+                warn!("Failed to check bootstrap nodes: {}", e);
+                return;
+            }
+        };
+
+        for node in &report.nodes {
+            if node.reachable {
+                info!("Bootstrap node reachable: {}", node.addresses.join(", "));
+            } else {
+                warn!("Bootstrap node unreachable: {}", node.addresses.join(", "));
+            }
+        }
+        if report.dht_table_size == 0 {
+            warn!("No DHT peers found after bootstrapping");
+        }
+
+        *LAST_REPORT.lock().await = Some(report.clone());
+        let _ = app_handle.emit(BOOTSTRAP_REPORT_EVENT, report);
+    });
+}
+
+pub async fn last_bootstrap_report() -> Option<BootstrapReport> {
+    LAST_REPORT.lock().await.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preset_records_are_valid() {
+        for preset in bootstrap_presets() {
+            for spr in &preset.nodes {
+                assert!(parse_spr(spr).is_ok(), "{}: {}", preset.name, spr);
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::features::bootstrap::{
+    add_bootstrap_node_with_handle, bootstrap_presets, last_bootstrap_report, parse_spr,
+    remove_bootstrap_node_with_handle, set_bootstrap_preset_with_handle,
+};
+use crate::features::settings::NodeProfile;
+use crate::features::shared::{map_storage_error, BootstrapPreset, BootstrapReport, SprInfo};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn list_bootstrap_presets() -> Result<Vec<BootstrapPreset>, String> {
+    Ok(bootstrap_presets())
+}
+
+#[tauri::command]
+pub async fn validate_bootstrap_spr(spr: String) -> Result<SprInfo, String> {
+    parse_spr(&spr).map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn add_bootstrap_node(spr: String, app_handle: AppHandle) -> Result<SprInfo, String> {
+    add_bootstrap_node_with_handle(spr, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn remove_bootstrap_node(
+    spr: String,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, String> {
+    remove_bootstrap_node_with_handle(spr, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn set_bootstrap_preset(
+    preset: String,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, String> {
+    set_bootstrap_preset_with_handle(preset, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn get_bootstrap_report() -> Result<Option<BootstrapReport>, String> {
+    Ok(last_bootstrap_report().await)
+}
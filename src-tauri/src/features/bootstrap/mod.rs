@@ -0,0 +1,7 @@
+pub mod bootstrap;
+pub mod commands;
+pub mod spr;
+
+pub use bootstrap::*;
+pub use commands::*;
+pub use spr::*;
@@ -0,0 +1,327 @@
+use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+use ed25519_dalek::Verifier;
+use sha2::{Digest, Sha256};
+
+use crate::features::shared::{SprInfo, StorageError};
+
+const SPR_PREFIX: &str = "spr:";
+/// Signing domain of peer records, nim-libp2p uses the multicodec name
+const RECORD_DOMAIN: &[u8] = b"libp2p-peer-record";
+/// Multicodec of a libp2p peer record payload
+const PEER_RECORD_TYPE: &[u8] = &[0x03, 0x01];
+
+const KEY_TYPE_ED25519: u64 = 1;
+const KEY_TYPE_SECP256K1: u64 = 2;
+
+/// Encoded keys up to this size are inlined into the peer ID instead of hashed
+const MAX_INLINE_KEY_LEN: usize = 42;
+
+fn invalid(reason: &str) -> StorageError {
+    StorageError::Configuration(format!("Invalid signed peer record: {}", reason))
+}
+
+/// Minimal protobuf reader, peer records only use varints and byte fields
+struct ProtoReader<'a> {
+    data: &'a [u8],
+}
+
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn varint(&mut self) -> Result<u64, StorageError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .data
+                .split_first()
+                .ok_or_else(|| invalid("truncated varint"))?;
+            self.data = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint too long"))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], StorageError> {
+        if self.data.len() < len {
+            return Err(invalid("truncated field"));
+        }
+        let (value, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(value)
+    }
+
+    fn field(&mut self) -> Result<Option<(u64, ProtoValue<'a>)>, StorageError> {
+        if self.data.is_empty() {
+            return Ok(None);
+        }
+
+        let key = self.varint()?;
+        let value = match key & 0x7 {
+            0 => ProtoValue::Varint(self.varint()?),
+            2 => {
+                let len = self.varint()? as usize;
+                ProtoValue::Bytes(self.bytes(len)?)
+            }
+            _ => return Err(invalid("unsupported protobuf wire type")),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Renders a binary multiaddr, only the protocols peer records use are supported
+fn decode_multiaddr(bytes: &[u8]) -> Result<String, StorageError> {
+    let mut reader = ProtoReader::new(bytes);
+    let mut out = String::new();
+
+    while !reader.data.is_empty() {
+        let code = reader.varint()?;
+        match code {
+            4 => {
+                let ip = reader.bytes(4)?;
+                out.push_str(&format!("/ip4/{}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3]));
+            }
+            41 => {
+                let ip: [u8; 16] = reader.bytes(16)?.try_into().unwrap_or_default();
+                out.push_str(&format!("/ip6/{}", std::net::Ipv6Addr::from(ip)));
+            }
+            6 | 273 => {
+                let port = reader.bytes(2)?;
+                let protocol = if code == 6 { "tcp" } else { "udp" };
+                out.push_str(&format!(
+                    "/{}/{}",
+                    protocol,
+                    u16::from_be_bytes([port[0], port[1]])
+                ));
+            }
+            53..=55 => {
+                let len = reader.varint()? as usize;
+                let host = String::from_utf8_lossy(reader.bytes(len)?).to_string();
+                let protocol = ["dns", "dns4", "dns6"][(code - 53) as usize];
+                out.push_str(&format!("/{}/{}", protocol, host));
+            }
+            460 => out.push_str("/quic"),
+            461 => out.push_str("/quic-v1"),
+            _ => return Err(invalid(&format!("unsupported multiaddr protocol {}", code))),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Derives the binary peer ID (a multihash) from a protobuf encoded public key
+fn peer_id_from_key(public_key: &[u8]) -> Vec<u8> {
+    let mut multihash = Vec::new();
+    if public_key.len() <= MAX_INLINE_KEY_LEN {
+        // Identity multihash
+        multihash.push(0x00);
+        write_varint(&mut multihash, public_key.len() as u64);
+        multihash.extend_from_slice(public_key);
+    } else {
+        // sha2-256 multihash
+        multihash.extend_from_slice(&[0x12, 0x20]);
+        multihash.extend_from_slice(&Sha256::digest(public_key));
+    }
+    multihash
+}
+
+fn verify_signature(
+    key_type: u64,
+    key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), StorageError> {
+    match key_type {
+        KEY_TYPE_ED25519 => {
+            let key: [u8; 32] = key
+                .try_into()
+                .map_err(|_| invalid("malformed ed25519 key"))?;
+            let signature: [u8; 64] = signature
+                .try_into()
+                .map_err(|_| invalid("malformed ed25519 signature"))?;
+            ed25519_dalek::VerifyingKey::from_bytes(&key)
+                .map_err(|_| invalid("malformed ed25519 key"))?
+                .verify(message, &ed25519_dalek::Signature::from_bytes(&signature))
+                .map_err(|_| invalid("signature does not match"))
+        }
+        KEY_TYPE_SECP256K1 => {
+            use k256::ecdsa::signature::Verifier as _;
+
+            let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                .map_err(|_| invalid("malformed secp256k1 key"))?;
+            // Records are signed with DER signatures, some encoders use the raw form
+            let signature = k256::ecdsa::Signature::from_der(signature)
+                .or_else(|_| k256::ecdsa::Signature::from_slice(signature))
+                .map_err(|_| invalid("malformed secp256k1 signature"))?;
+            key.verify(message, &signature)
+                .map_err(|_| invalid("signature does not match"))
+        }
+        _ => Err(invalid("unsupported key type")),
+    }
+}
+
+/// Decodes a signed peer record and checks its signature
+pub fn parse_spr(spr: &str) -> Result<SprInfo, StorageError> {
+    let spr = spr.trim();
+    let encoded = spr
+        .strip_prefix(SPR_PREFIX)
+        .ok_or_else(|| invalid("missing spr: prefix"))?;
+    let envelope = URL_SAFE_NO_PAD
+        .decode(encoded.trim_end_matches('='))
+        .or_else(|_| URL_SAFE.decode(encoded))
+        .map_err(|_| invalid("not base64"))?;
+
+    let mut public_key = None;
+    let mut payload_type = None;
+    let mut payload = None;
+    let mut signature = None;
+
+    let mut reader = ProtoReader::new(&envelope);
+    while let Some((field, value)) = reader.field()? {
+        match (field, value) {
+            (1, ProtoValue::Bytes(bytes)) => public_key = Some(bytes),
+            (2, ProtoValue::Bytes(bytes)) => payload_type = Some(bytes),
+            (3, ProtoValue::Bytes(bytes)) => payload = Some(bytes),
+            (5, ProtoValue::Bytes(bytes)) => signature = Some(bytes),
+            _ => {}
+        }
+    }
+
+    let public_key = public_key.ok_or_else(|| invalid("missing public key"))?;
+    let payload_type = payload_type.ok_or_else(|| invalid("missing payload type"))?;
+    let payload = payload.ok_or_else(|| invalid("missing payload"))?;
+    let signature = signature.ok_or_else(|| invalid("missing signature"))?;
+
+    if payload_type != PEER_RECORD_TYPE {
+        return Err(invalid("not a peer record"));
+    }
+
+    let mut key_type = None;
+    let mut key_data = None;
+    let mut reader = ProtoReader::new(public_key);
+    while let Some((field, value)) = reader.field()? {
+        match (field, value) {
+            (1, ProtoValue::Varint(value)) => key_type = Some(value),
+            (2, ProtoValue::Bytes(bytes)) => key_data = Some(bytes),
+            _ => {}
+        }
+    }
+    let key_type = key_type.ok_or_else(|| invalid("missing key type"))?;
+    let key_data = key_data.ok_or_else(|| invalid("missing key data"))?;
+
+    let mut signed = Vec::new();
+    for part in [RECORD_DOMAIN, payload_type, payload] {
+        write_varint(&mut signed, part.len() as u64);
+        signed.extend_from_slice(part);
+    }
+    verify_signature(key_type, key_data, &signed, signature)?;
+
+    let mut peer_id = None;
+    let mut sequence = 0;
+    let mut addresses = Vec::new();
+    let mut reader = ProtoReader::new(payload);
+    while let Some((field, value)) = reader.field()? {
+        match (field, value) {
+            (1, ProtoValue::Bytes(bytes)) => peer_id = Some(bytes),
+            (2, ProtoValue::Varint(value)) => sequence = value,
+            (3, ProtoValue::Bytes(address_info)) => {
+                let mut info = ProtoReader::new(address_info);
+                while let Some((field, value)) = info.field()? {
+                    if let (1, ProtoValue::Bytes(multiaddr)) = (field, value) {
+                        addresses.push(decode_multiaddr(multiaddr)?);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let peer_id = peer_id.ok_or_else(|| invalid("missing peer ID"))?;
+    if peer_id != peer_id_from_key(public_key) {
+        return Err(invalid("peer ID does not match the signing key"));
+    }
+    if addresses.is_empty() {
+        return Err(invalid("record has no addresses"));
+    }
+
+    Ok(SprInfo {
+        spr: spr.to_string(),
+        key_type: match key_type {
+            KEY_TYPE_ED25519 => "ed25519".to_string(),
+            _ => "secp256k1".to_string(),
+        },
+        public_key: URL_SAFE_NO_PAD.encode(key_data),
+        peer_id: bs58::encode(peer_id).into_string(),
+        sequence,
+        addresses,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bootstrap record of a Codex testnet node
+    const CODEX_SPR: &str = "spr:CiUIAhIhAiJvIcA_ZwPZ9ugVKDbmqwhJZaig5zKyLiuaicRcCGqLEgIDARo8CicAJQgCEiECIm8hwD9nA9n26BUoNuarCEllqKDnMrIuK5qJxFwIaosQ3d6esAYaCwoJBJ_f8zKRAnU6KkYwRAIgM0MvWNJL296kJ9gWvfatfmVvT-A7O2s8Mxp8l9c8EW0CIC-h-H-jBVSgFjg3Eny2u33qF7BDnWFzo7fGfZ7_qc9P";
+
+    fn reencode(envelope: &[u8]) -> String {
+        format!("{}{}", SPR_PREFIX, URL_SAFE_NO_PAD.encode(envelope))
+    }
+
+    fn envelope() -> Vec<u8> {
+        URL_SAFE_NO_PAD
+            .decode(CODEX_SPR.strip_prefix(SPR_PREFIX).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_codex_record() {
+        let info = parse_spr(CODEX_SPR).unwrap();
+        assert_eq!(info.key_type, "secp256k1");
+        assert_eq!(info.sequence, 1711779677);
+        assert_eq!(info.addresses, vec!["/ip4/159.223.243.50/udp/30010"]);
+        assert!(info.peer_id.starts_with("16Uiu2"));
+    }
+
+    #[test]
+    fn rejects_tampered_signature() {
+        let mut envelope = envelope();
+        // The signature is the last field of the envelope
+        *envelope.last_mut().unwrap() ^= 0x01;
+        assert!(parse_spr(&reencode(&envelope)).is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_payload() {
+        let spr = CODEX_SPR.replace("BJ_f8zKRAnU6", "BJ_f8zORAnU6");
+        assert!(parse_spr(&spr).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_record() {
+        let envelope = envelope();
+        for len in [0, 1, envelope.len() / 2, envelope.len() - 1] {
+            assert!(parse_spr(&reencode(&envelope[..len])).is_err());
+        }
+        assert!(parse_spr("spr:").is_err());
+        assert!(parse_spr(&CODEX_SPR[4..]).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::features::shared::StorageError;
+
+/// Marks content produced by [`compress_file`], followed by the format version
+pub const COMPRESSION_MAGIC: &[u8; 6] = b"SMZST\x01";
+
+/// Manifest mimetype of compressed uploads. Downloads only look for the header when
+/// the manifest carries it, plain files may start with the magic by chance.
+pub const COMPRESSED_MIMETYPE: &str = "application/vnd.storeman.zstd";
+
+/// Magic followed by the original size
+pub const COMPRESSION_HEADER_LEN: usize = COMPRESSION_MAGIC.len() + 8;
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Returns the original size recorded in the compression header, or `None` if the
+/// file is not compressed by storeman
+pub fn read_compressed_header(path: &Path) -> Result<Option<u64>, StorageError> {
+    let mut file = File::open(path).map_err(|e| StorageError::Io(e.to_string()))?;
+    let mut header = [0u8; COMPRESSION_HEADER_LEN];
+    match file.read_exact(&mut header) {
+        Ok(()) if &header[..COMPRESSION_MAGIC.len()] == COMPRESSION_MAGIC => {
+            let mut original_size = [0u8; 8];
+            original_size.copy_from_slice(&header[COMPRESSION_MAGIC.len()..]);
+            Ok(Some(u64::from_le_bytes(original_size)))
+        }
+        Ok(()) => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(StorageError::Io(e.to_string())),
+    }
+}
+
+/// Compresses `src` into `dst` with zstd, returning the compressed size.
+///
+/// Layout: magic | original size (u64 LE) | zstd frame.
+pub fn compress_file(src: &Path, dst: &Path) -> Result<u64, StorageError> {
+    let original_size = std::fs::metadata(src)
+        .map_err(|e| StorageError::Io(e.to_string()))?
+        .len();
+
+    let reader = BufReader::new(File::open(src).map_err(|e| StorageError::Io(e.to_string()))?);
+    let mut writer =
+        BufWriter::new(File::create(dst).map_err(|e| StorageError::Io(e.to_string()))?);
+    writer
+        .write_all(COMPRESSION_MAGIC)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    writer
+        .write_all(&original_size.to_le_bytes())
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    zstd::stream::copy_encode(reader, &mut writer, COMPRESSION_LEVEL)
+        .map_err(|e| StorageError::Upload(format!("Compression failed: {}", e)))?;
+    writer
+        .flush()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    std::fs::metadata(dst)
+        .map(|metadata| metadata.len())
+        .map_err(|e| StorageError::Io(e.to_string()))
+}
+
+/// Decompresses a file produced by [`compress_file`], returning the original size.
+///
+/// Output stops one byte past the size recorded in the header, which the uploader
+/// chose, so this only catches streams that disagree with their own header.
+/// `dst` is removed again if the stream is corrupted or does not match the recorded size.
+pub fn decompress_file(src: &Path, dst: &Path) -> Result<u64, StorageError> {
+    let result = decompress_file_inner(src, dst);
+    if result.is_err() {
+        let _ = std::fs::remove_file(dst);
+    }
+    result
+}
+
+fn decompress_file_inner(src: &Path, dst: &Path) -> Result<u64, StorageError> {
+    let expected_size = read_compressed_header(src)?.ok_or_else(|| {
+        StorageError::Download("Content is not compressed by storeman".to_string())
+    })?;
+
+    let mut reader = BufReader::new(File::open(src).map_err(|e| StorageError::Io(e.to_string()))?);
+    let mut header = [0u8; COMPRESSION_HEADER_LEN];
+    reader
+        .read_exact(&mut header)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let decoder = zstd::stream::read::Decoder::with_buffer(reader)
+        .map_err(|e| StorageError::Download(format!("Decompression failed: {}", e)))?;
+    // One byte past the recorded size is enough to tell the stream is too long
+    let mut limited = decoder.take(expected_size.saturating_add(1));
+
+    let mut writer =
+        BufWriter::new(File::create(dst).map_err(|e| StorageError::Io(e.to_string()))?);
+    let size = std::io::copy(&mut limited, &mut writer)
+        .map_err(|e| StorageError::Download(format!("Decompression failed: {}", e)))?;
+    if size > expected_size {
+        return Err(StorageError::Download(format!(
+            "Decompressed content exceeds the recorded {} bytes",
+            expected_size
+        )));
+    }
+    writer
+        .flush()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    if size != expected_size {
+        return Err(StorageError::Download(format!(
+            "Decompressed {} bytes but expected {}",
+            size, expected_size
+        )));
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Temporary directory removed again when the test ends
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("storeman-zst-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self, name: &str, contents: &[u8]) -> PathBuf {
+            let path = self.0.join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 7) as u8).collect()
+    }
+
+    /// Compresses `plaintext` and returns the compressed bytes
+    fn compressed(scratch: &Scratch, plaintext: &[u8]) -> Vec<u8> {
+        let src = scratch.file("plain", plaintext);
+        let packed = scratch.0.join("packed");
+        compress_file(&src, &packed).unwrap();
+        std::fs::read(&packed).unwrap()
+    }
+
+    fn with_recorded_size(mut bytes: Vec<u8>, size: u64) -> Vec<u8> {
+        bytes[COMPRESSION_MAGIC.len()..COMPRESSION_HEADER_LEN].copy_from_slice(&size.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn round_trips() {
+        let scratch = Scratch::new();
+        let plaintext = sample(256 * 1024);
+        let src = scratch.file("plain", &plaintext);
+        let packed = scratch.0.join("packed");
+        let unpacked = scratch.0.join("unpacked");
+
+        let packed_len = compress_file(&src, &packed).unwrap();
+        assert!(packed_len < plaintext.len() as u64);
+        assert_eq!(
+            read_compressed_header(&packed).unwrap(),
+            Some(plaintext.len() as u64)
+        );
+        assert_eq!(
+            decompress_file(&packed, &unpacked).unwrap(),
+            plaintext.len() as u64
+        );
+        assert_eq!(std::fs::read(&unpacked).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn ignores_files_without_header() {
+        let scratch = Scratch::new();
+        assert_eq!(
+            read_compressed_header(&scratch.file("plain", b"plain text")).unwrap(),
+            None
+        );
+        assert_eq!(
+            read_compressed_header(&scratch.file("short", &COMPRESSION_MAGIC[..3])).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_stream() {
+        let scratch = Scratch::new();
+        let bytes = compressed(&scratch, &sample(256 * 1024));
+        let unpacked = scratch.0.join("unpacked");
+        for len in [COMPRESSION_HEADER_LEN, bytes.len() / 2, bytes.len() - 1] {
+            let packed = scratch.file("truncated", &bytes[..len]);
+            assert!(decompress_file(&packed, &unpacked).is_err());
+            assert!(!unpacked.exists());
+        }
+    }
+
+    #[test]
+    fn rejects_sizes_that_disagree_with_the_stream() {
+        let scratch = Scratch::new();
+        let plaintext = sample(64 * 1024);
+        let bytes = compressed(&scratch, &plaintext);
+        let unpacked = scratch.0.join("unpacked");
+
+        // A header claiming more than the stream holds
+        let oversized = with_recorded_size(bytes.clone(), u64::MAX);
+        let packed = scratch.file("oversized", &oversized);
+        assert!(decompress_file(&packed, &unpacked).is_err());
+        assert!(!unpacked.exists());
+
+        // A stream expanding past its header stops one byte after the recorded size
+        let undersized = with_recorded_size(bytes, 1000);
+        let packed = scratch.file("undersized", &undersized);
+        assert!(decompress_file(&packed, &unpacked).is_err());
+        assert!(!unpacked.exists());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod compression;
+
+pub use compression::*;
@@ -1,5 +1,11 @@
-use crate::features::connection::get_storage_manager_with_handle;
-use crate::features::shared::{map_storage_error, NodeInfo, StorageConnectionStatus};
+use crate::features::bootstrap::schedule_bootstrap_report;
+use crate::features::connection::{
+    ensure_ports_available, get_storage_manager_with_handle, resolve_port_conflicts_with_handle,
+    set_transfers_paused, transfers_paused,
+};
+use crate::features::peers::is_peer_banned;
+use crate::features::settings::NodeProfile;
+use crate::features::shared::{map_storage_error, NodeInfo, StorageConnectionStatus, StorageError};
 use tauri::AppHandle;
 
 #[tauri::command]
@@ -20,10 +26,33 @@ pub async fn get_node_info(app_handle: AppHandle) -> Result<NodeInfo, String> {
 
 #[tauri::command]
 pub async fn start_node(app_handle: AppHandle) -> Result<(), String> {
-    let manager = get_storage_manager_with_handle(Some(app_handle))
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone()))
+        .await
+        .map_err(map_storage_error)?;
+    ensure_ports_available(&app_handle)
         .await
         .map_err(map_storage_error)?;
-    manager.start_node().await.map_err(map_storage_error)
+    manager.start_node().await.map_err(map_storage_error)?;
+    schedule_bootstrap_report(app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resolve_port_conflicts(app_handle: AppHandle) -> Result<NodeProfile, String> {
+    resolve_port_conflicts_with_handle(app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn pause_transfers(paused: bool) -> Result<(), String> {
+    set_transfers_paused(paused);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_transfers_paused() -> Result<bool, String> {
+    Ok(transfers_paused())
 }
 
 #[tauri::command]
@@ -40,6 +69,13 @@ pub async fn connect_to_peer(
     addresses: Vec<String>,
     app_handle: AppHandle,
 ) -> Result<(), String> {
+    if is_peer_banned(&peer_id, &app_handle).await {
+        return Err(map_storage_error(StorageError::Configuration(format!(
+            "Peer {} is banned, unban it first",
+            peer_id
+        ))));
+    }
+
     let manager = get_storage_manager_with_handle(Some(app_handle))
         .await
         .map_err(map_storage_error)?;
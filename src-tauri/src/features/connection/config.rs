@@ -1,37 +1,144 @@
 use codex_bindings::node::config::RepoKind;
 use codex_bindings::{CodexConfig, LogLevel};
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
+use tracing::{debug, info};
 
-/// Creates a CodexConfig using the app handle for proper application data storage
-pub fn create_codex_config(app_handle: &AppHandle) -> CodexConfig {
-    // Use app_data_dir for proper application data storage
-    let data_dir = app_handle
+use crate::features::bootstrap::bootstrap_nodes_for;
+use crate::features::logging::current_log_level;
+use crate::features::settings::{
+    load_settings, LogLevelSetting, NodeProfile, RepoBackend, DEFAULT_PROFILE,
+};
+use crate::features::shared::StorageError;
+
+/// Returns the node data directory of a profile
+pub fn profile_data_dir(app_handle: &AppHandle, profile_name: &str) -> PathBuf {
+    let app_data_dir = app_handle
         .path()
         .app_data_dir()
-        .expect("Failed to get app data directory")
-        .join("node_data");
+        .expect("Failed to get app data directory");
+
+    // The default profile keeps the original location so existing installs keep their identity
+    if profile_name == DEFAULT_PROFILE {
+        app_data_dir.join("node_data")
+    } else {
+        app_data_dir
+            .join("profiles")
+            .join(profile_name)
+            .join("node_data")
+    }
+}
+
+fn repo_kind(backend: RepoBackend) -> RepoKind {
+    match backend {
+        RepoBackend::LevelDb => RepoKind::LevelDb,
+        RepoBackend::Sqlite => RepoKind::Sqlite,
+        RepoBackend::Fs => RepoKind::Fs,
+    }
+}
+
+pub fn node_log_level(level: LogLevelSetting) -> LogLevel {
+    match level {
+        LogLevelSetting::Trace => LogLevel::Trace,
+        LogLevelSetting::Debug => LogLevel::Debug,
+        LogLevelSetting::Info => LogLevel::Info,
+        LogLevelSetting::Warn => LogLevel::Warn,
+        LogLevelSetting::Error => LogLevel::Error,
+    }
+}
+
+fn listen_addrs(profile: &NodeProfile) -> Vec<String> {
+    if profile.listen_addrs.is_empty() {
+        vec![format!("/ip4/0.0.0.0/tcp/{}", profile.listen_port)]
+    } else {
+        profile.listen_addrs.clone()
+    }
+}
+
+/// Checks the listen, announce and NAT settings of a profile before it is saved
+pub fn validate_network_settings(profile: &NodeProfile) -> Result<(), StorageError> {
+    for address in profile.listen_addrs.iter().chain(&profile.announce_addrs) {
+        let is_multiaddr = address.starts_with('/') && address.split('/').count() >= 3;
+        if !is_multiaddr {
+            return Err(StorageError::Configuration(format!(
+                "'{}' is not a multiaddr",
+                address
+            )));
+        }
+    }
+
+    let nat_is_valid = match profile.nat.split_once(':') {
+        Some(("extip", ip)) => ip.parse::<std::net::IpAddr>().is_ok(),
+        Some(_) => false,
+        None => matches!(profile.nat.as_str(), "any" | "none" | "upnp" | "pmp"),
+    };
+    if !nat_is_valid {
+        return Err(StorageError::Configuration(format!(
+            "Invalid NAT setting '{}', use any, none, upnp, pmp or extip:<ip>",
+            profile.nat
+        )));
+    }
+
+    Ok(())
+}
+
+/// Creates a CodexConfig for the active profile
+pub fn create_codex_config(app_handle: &AppHandle) -> Result<CodexConfig, StorageError> {
+    let profile = load_settings(app_handle).active_profile();
+    create_codex_config_for_profile(app_handle, &profile)
+}
+
+/// Creates a CodexConfig using the app handle for proper application data storage
+pub fn create_codex_config_for_profile(
+    app_handle: &AppHandle,
+    profile: &NodeProfile,
+) -> Result<CodexConfig, StorageError> {
+    // Use app_data_dir for proper application data storage
+    create_codex_config_at(&profile_data_dir(app_handle, &profile.name), profile)
+}
 
-    println!("Storage data directory: {}", data_dir.display());
+/// Creates a CodexConfig for `profile` with its repository in `data_dir`
+pub fn create_codex_config_at(
+    data_dir: &Path,
+    profile: &NodeProfile,
+) -> Result<CodexConfig, StorageError> {
+    info!("Storage data directory: {}", data_dir.display());
 
-    // Ensure the directory exists using std::fs
-    if let Err(e) = std::fs::create_dir_all(&data_dir) {
-        panic!(
+    std::fs::create_dir_all(data_dir).map_err(|e| {
+        StorageError::Io(format!(
             "Failed to create data directory {}: {}",
             data_dir.display(),
             e
-        );
+        ))
+    })?;
+    debug!(
+        "Successfully created data directory: {}",
+        data_dir.display()
+    );
+
+    // LAN mode must not map ports or announce a public address through the router
+    let nat = if profile.lan_mode {
+        "none".to_string()
     } else {
-        println!(
-            "Successfully created data directory: {}",
-            data_dir.display()
-        );
-    }
+        profile.nat.clone()
+    };
+    let config = CodexConfig::new()
+        .log_level(node_log_level(current_log_level()))
+        .data_dir(data_dir)
+        .storage_quota(profile.storage_quota)
+        .max_peers(profile.max_peers)
+        .discovery_port(profile.discovery_port)
+        .listen_addrs(listen_addrs(profile))
+        .nat(nat)
+        .repo_kind(repo_kind(profile.repo_backend));
+    let config = if profile.announce_addrs.is_empty() {
+        config
+    } else {
+        config.announce_addrs(profile.announce_addrs.clone())
+    };
 
-    CodexConfig::new()
-        .log_level(LogLevel::Debug)
-        .data_dir(&data_dir)
-        .storage_quota(1024 * 1024 * 1024) // 1 GB
-        .max_peers(50)
-        .discovery_port(8089)
-        .repo_kind(RepoKind::LevelDb)
+    Ok(match bootstrap_nodes_for(profile) {
+        Some(nodes) => config.bootstrap_nodes(nodes),
+        None => config,
+    })
 }
@@ -1,13 +1,60 @@
 use codex_bindings::{connect, debug, CodexNode};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{Mutex, OnceCell, RwLock};
+use tracing::{error, info, warn};
+
+use crate::features::instance::explain_node_creation_error;
+use crate::features::shared::{
+    NodeInfo, OperationRecord, OperationStage, StorageConnectionStatus, StorageError,
+    TransferCounters,
+};
+use crate::features::shutdown::shutdown_started;
+
+// Finished operations kept for diagnostics
+const OPERATION_HISTORY_LIMIT: usize = 200;
+// Finished operation IDs remembered to drop progress that arrives after the end
+const FINISHED_TRANSFER_LIMIT: usize = 1024;
+
+/// Direction and bytes seen so far of an operation that is still running
+struct ActiveTransfer {
+    uploading: Option<bool>,
+    bytes: usize,
+    started: Instant,
+}
 
-use crate::features::shared::{NodeInfo, StorageConnectionStatus, StorageError};
+/// Operations that already finished. Progress callbacks run on spawned tasks and can
+/// arrive after `Completed`, they must not start counting the transfer again.
+#[derive(Default)]
+struct FinishedTransfers {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl FinishedTransfers {
+    fn contains(&self, operation_id: &str) -> bool {
+        self.ids.contains(operation_id)
+    }
+
+    fn insert(&mut self, operation_id: &str) {
+        if !self.ids.insert(operation_id.to_string()) {
+            return;
+        }
+        self.order.push_back(operation_id.to_string());
+        if self.order.len() > FINISHED_TRANSFER_LIMIT {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+    }
+}
 
 pub struct StorageManager {
     node: Arc<Mutex<Option<CodexNode>>>,
-    config: codex_bindings::CodexConfig,
+    config: Arc<RwLock<codex_bindings::CodexConfig>>,
     status: Arc<RwLock<StorageConnectionStatus>>,
     progress_senders: Arc<
         Mutex<
@@ -17,15 +64,23 @@ pub struct StorageManager {
             >,
         >,
     >,
+    history: Arc<Mutex<VecDeque<OperationRecord>>>,
+    active_transfers: Arc<Mutex<HashMap<String, ActiveTransfer>>>,
+    finished_transfers: Arc<Mutex<FinishedTransfers>>,
+    counters: Arc<Mutex<TransferCounters>>,
 }
 
 impl StorageManager {
     pub async fn new(config: codex_bindings::CodexConfig) -> Result<Self, StorageError> {
         let manager = Self {
             node: Arc::new(Mutex::new(None)),
-            config,
+            config: Arc::new(RwLock::new(config)),
             status: Arc::new(RwLock::new(StorageConnectionStatus::Disconnected)),
             progress_senders: Arc::new(Mutex::new(HashMap::new())),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            active_transfers: Arc::new(Mutex::new(HashMap::new())),
+            finished_transfers: Arc::new(Mutex::new(FinishedTransfers::default())),
+            counters: Arc::new(Mutex::new(TransferCounters::default())),
         };
 
         manager.initialize_node().await?;
@@ -49,7 +104,8 @@ impl StorageManager {
             }
         }
 
-        let node = match CodexNode::new(self.config.clone()) {
+        let config = self.config.read().await.clone();
+        let node = match CodexNode::new(config) {
             Ok(node) => node,
             Err(e) => {
                 return Err(StorageError::NodeCreation(e.to_string()));
@@ -127,7 +183,7 @@ impl StorageManager {
 
             if let Some(mut node) = node_option {
                 if let Err(e) = node.stop() {
-                    eprintln!("Failed to stop node: {}", e);
+                    error!("Failed to stop node: {}", e);
                 }
                 // Put the stopped node back
                 let mut node_guard = self.node.lock().await;
@@ -143,6 +199,161 @@ impl StorageManager {
         Ok(())
     }
 
+    /// Replaces the node with one built from `config`, e.g. when switching profiles.
+    ///
+    /// The current node is stopped and destroyed first, and the new one is started
+    /// again only if the old one was running. Refused while transfers are running,
+    /// and if the new node cannot be brought up the previous configuration and node
+    /// are restored.
+    pub async fn switch_config(
+        &self,
+        config: codex_bindings::CodexConfig,
+    ) -> Result<(), StorageError> {
+        // Stopping the node would fail every running upload and download
+        let running = self.active_operation_count().await;
+        if running > 0 {
+            warn!(
+                "Not replacing the node while {} transfer(s) are running",
+                running
+            );
+            return Err(StorageError::NodeRunning);
+        }
+
+        let was_started = {
+            let node_guard = self.node.lock().await;
+            node_guard
+                .as_ref()
+                .map(|node| node.is_started())
+                .unwrap_or(false)
+        };
+
+        self.release_node().await?;
+
+        let previous = std::mem::replace(&mut *self.config.write().await, config);
+
+        if let Err(e) = self.bring_up(was_started).await {
+            error!(
+                "Failed to apply the node configuration, restoring the previous one: {}",
+                e
+            );
+            let _ = self.release_node().await;
+            *self.config.write().await = previous;
+            if let Err(restore) = self.bring_up(was_started).await {
+                error!("Failed to restore the previous node: {}", restore);
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Initializes the node from the current config and starts it if asked to
+    async fn bring_up(&self, start: bool) -> Result<(), StorageError> {
+        self.initialize_node().await?;
+        if start {
+            self.start_node().await?;
+        }
+        Ok(())
+    }
+
+    /// Stops and destroys the node so its repository files are released.
+    ///
+    /// `initialize_node` brings a node back afterwards.
+    pub async fn release_node(&self) -> Result<(), StorageError> {
+        self.stop_node().await?;
+
+        {
+            let node_option = {
+                let mut node_guard = self.node.lock().await;
+                node_guard.take()
+            };
+
+            if let Some(node) = node_option {
+                if let Err(e) = node.destroy() {
+                    error!("Failed to destroy node: {}", e);
+                }
+            }
+        }
+
+        {
+            let mut status = self.status.write().await;
+            *status = StorageConnectionStatus::Disconnected;
+        }
+
+        Ok(())
+    }
+
+    /// Destroys the node, deletes `data_dir` and initializes a fresh node on it.
+    ///
+    /// Files named in `keep_files` survive the reset. The node has to be stopped
+    /// first so the repository is never wiped underneath it. The old directory is
+    /// only moved aside until the new one is ready, and put back if that fails.
+    pub async fn reset_repository(
+        &self,
+        data_dir: &Path,
+        keep_files: &[&str],
+    ) -> Result<(), StorageError> {
+        {
+            let node_guard = self.node.lock().await;
+            if node_guard
+                .as_ref()
+                .map(|node| node.is_started())
+                .unwrap_or(false)
+            {
+                return Err(StorageError::NodeRunning);
+            }
+        }
+
+        {
+            let node_option = {
+                let mut node_guard = self.node.lock().await;
+                node_guard.take()
+            };
+
+            if let Some(node) = node_option {
+                if let Err(e) = node.destroy() {
+                    error!("Failed to destroy node: {}", e);
+                }
+            }
+        }
+
+        {
+            let mut status = self.status.write().await;
+            *status = StorageConnectionStatus::Disconnected;
+        }
+
+        let old_dir = data_dir.with_extension("resetting");
+        if old_dir.exists() {
+            std::fs::remove_dir_all(&old_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+        if data_dir.exists() {
+            std::fs::rename(data_dir, &old_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+
+        if let Err(e) = recreate_data_dir(data_dir, &old_dir, keep_files) {
+            if old_dir.exists() {
+                let _ = std::fs::remove_dir_all(data_dir);
+                if let Err(restore_error) = std::fs::rename(&old_dir, data_dir) {
+                    error!(
+                        "Failed to restore {} from {}: {}",
+                        data_dir.display(),
+                        old_dir.display(),
+                        restore_error
+                    );
+                }
+            }
+            return Err(e);
+        }
+
+        if let Err(e) = std::fs::remove_dir_all(&old_dir) {
+            if old_dir.exists() {
+                warn!("Failed to remove {}: {}", old_dir.display(), e);
+            }
+        }
+
+        self.initialize_node().await
+    }
+
     pub async fn get_status(&self) -> StorageConnectionStatus {
         self.status.read().await.clone()
     }
@@ -217,12 +428,102 @@ impl StorageManager {
         operation_id: &str,
         progress: crate::features::shared::ProgressMessage,
     ) {
+        self.record_transfer(operation_id, &progress.stage, progress.bytes_processed)
+            .await;
+
+        if matches!(
+            progress.stage,
+            OperationStage::Completed | OperationStage::Failed(_)
+        ) {
+            let mut history = self.history.lock().await;
+            if history.len() == OPERATION_HISTORY_LIMIT {
+                history.pop_front();
+            }
+            history.push_back(OperationRecord {
+                finished_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default(),
+                progress: progress.clone(),
+            });
+        }
+
         let senders = self.progress_senders.lock().await;
         if let Some(sender) = senders.get(operation_id) {
             let _ = sender.send(progress);
         }
     }
 
+    /// Folds a progress update into the transfer counters
+    async fn record_transfer(&self, operation_id: &str, stage: &OperationStage, bytes: usize) {
+        let mut finished = self.finished_transfers.lock().await;
+        if finished.contains(operation_id) {
+            return;
+        }
+        let mut active = self.active_transfers.lock().await;
+        let transfer = active
+            .entry(operation_id.to_string())
+            .or_insert_with(|| ActiveTransfer {
+                uploading: None,
+                bytes: 0,
+                started: Instant::now(),
+            });
+
+        let mut counters = self.counters.lock().await;
+        match stage {
+            OperationStage::Uploading | OperationStage::Downloading => {
+                let uploading = matches!(stage, OperationStage::Uploading);
+                transfer.uploading = Some(uploading);
+                // Progress reports cumulative bytes, only count what is new
+                let delta = bytes.saturating_sub(transfer.bytes) as u64;
+                transfer.bytes = transfer.bytes.max(bytes);
+                if uploading {
+                    counters.bytes_out += delta;
+                } else {
+                    counters.bytes_in += delta;
+                }
+            }
+            OperationStage::Completed => {
+                let duration = transfer.started.elapsed().as_millis() as u64;
+                match transfer.uploading {
+                    Some(true) => {
+                        counters.uploads_completed += 1;
+                        counters.upload_duration_ms += duration;
+                    }
+                    Some(false) => {
+                        counters.downloads_completed += 1;
+                        counters.download_duration_ms += duration;
+                    }
+                    None => {}
+                }
+                active.remove(operation_id);
+                finished.insert(operation_id);
+            }
+            OperationStage::Failed(_) => {
+                if transfer.uploading.is_some() {
+                    counters.failed += 1;
+                }
+                active.remove(operation_id);
+                finished.insert(operation_id);
+            }
+            OperationStage::Initializing | OperationStage::Verifying => {}
+        }
+    }
+
+    pub async fn transfer_counters(&self) -> TransferCounters {
+        self.counters.lock().await.clone()
+    }
+
+    /// Returns the most recently finished operations, oldest first
+    pub async fn operation_history(&self) -> Vec<OperationRecord> {
+        self.history.lock().await.iter().cloned().collect()
+    }
+
+    /// Returns the configuration the current node was built from
+    pub async fn config(&self) -> codex_bindings::CodexConfig {
+        self.config.read().await.clone()
+    }
+
     pub async fn register_progress_sender(
         &self,
         operation_id: String,
@@ -235,9 +536,77 @@ impl StorageManager {
         rx
     }
 
+    /// Registers a progress sender that stays registered until the returned guard is dropped.
+    ///
+    /// Refuses new operations once the app is shutting down. The check runs after
+    /// registering, so the shutdown either waits for the operation or refuses it.
+    pub async fn track_progress(
+        &self,
+        operation_id: String,
+    ) -> Result<ProgressRegistration, StorageError> {
+        let receiver = self.register_progress_sender(operation_id.clone()).await;
+        let registration = ProgressRegistration {
+            manager: self.clone(),
+            operation_id,
+            _receiver: receiver,
+        };
+        if shutdown_started() {
+            return Err(StorageError::ShuttingDown);
+        }
+        Ok(registration)
+    }
+
+    /// Number of uploads and downloads that have not finished yet
+    pub async fn active_operation_count(&self) -> usize {
+        self.progress_senders.lock().await.len()
+    }
+
     pub async fn unregister_progress_sender(&self, operation_id: &str) {
         let mut senders = self.progress_senders.lock().await;
         senders.remove(operation_id);
+        let mut finished = self.finished_transfers.lock().await;
+        finished.insert(operation_id);
+        self.active_transfers.lock().await.remove(operation_id);
+    }
+}
+
+/// Creates an empty `data_dir` and copies the files in `keep_files` over from `old_dir`
+fn recreate_data_dir(
+    data_dir: &Path,
+    old_dir: &Path,
+    keep_files: &[&str],
+) -> Result<(), StorageError> {
+    std::fs::create_dir_all(data_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    for name in keep_files {
+        let kept = old_dir.join(name);
+        if kept.is_file() {
+            std::fs::copy(&kept, data_dir.join(name))
+                .map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Unregisters the progress sender of an operation when dropped, so early returns
+/// and errors cannot leave the operation counted as active
+pub struct ProgressRegistration {
+    manager: StorageManager,
+    operation_id: String,
+    _receiver: tokio::sync::mpsc::UnboundedReceiver<crate::features::shared::ProgressMessage>,
+}
+
+impl Drop for ProgressRegistration {
+    fn drop(&mut self) {
+        let manager = self.manager.clone();
+        let operation_id = std::mem::take(&mut self.operation_id);
+        // Drop the sender right away when possible so the active count is exact for
+        // whatever runs next, e.g. a config switch after a migration export
+        if let Ok(mut senders) = manager.progress_senders.try_lock() {
+            senders.remove(&operation_id);
+        }
+        tauri::async_runtime::spawn(async move {
+            manager.unregister_progress_sender(&operation_id).await;
+        });
     }
 }
 
@@ -245,13 +614,38 @@ impl Clone for StorageManager {
     fn clone(&self) -> Self {
         Self {
             node: Arc::clone(&self.node),
-            config: self.config.clone(),
+            config: Arc::clone(&self.config),
             status: Arc::clone(&self.status),
             progress_senders: Arc::clone(&self.progress_senders),
+            history: Arc::clone(&self.history),
+            active_transfers: Arc::clone(&self.active_transfers),
+            finished_transfers: Arc::clone(&self.finished_transfers),
+            counters: Arc::clone(&self.counters),
         }
     }
 }
 
+// Background transfers, watch folders, mirror subscriptions and the gateway, hold off while set
+static TRANSFERS_PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn transfers_paused() -> bool {
+    TRANSFERS_PAUSED.load(Ordering::SeqCst)
+}
+
+/// Pauses or resumes background transfers, transfers already running are not interrupted.
+/// They stay paused once the app is shutting down.
+pub fn set_transfers_paused(paused: bool) {
+    if !paused && shutdown_started() {
+        warn!("Not resuming background transfers, the app is shutting down");
+        return;
+    }
+    TRANSFERS_PAUSED.store(paused, Ordering::SeqCst);
+    info!(
+        "Background transfers {}",
+        if paused { "paused" } else { "resumed" }
+    );
+}
+
 // Global manager instance
 pub static STORAGE_MANAGER: OnceCell<Arc<StorageManager>> = OnceCell::const_new();
 
@@ -261,14 +655,23 @@ pub async fn get_storage_manager_with_handle(
     if let Some(manager) = STORAGE_MANAGER.get() {
         Ok(Arc::clone(manager))
     } else {
-        let config = if let Some(handle) = app_handle {
-            crate::features::connection::create_codex_config(&handle)
-        } else {
-            return Err(StorageError::Configuration(
+        let handle = app_handle.ok_or_else(|| {
+            StorageError::Configuration(
                 "App handle is required to create storage manager".to_string(),
-            ));
+            )
+        })?;
+        let config = crate::features::connection::create_codex_config(&handle)?;
+        let manager = match StorageManager::new(config).await {
+            Ok(manager) => Arc::new(manager),
+            Err(e) => {
+                let settings = crate::features::settings::load_settings(&handle);
+                let data_dir = crate::features::connection::profile_data_dir(
+                    &handle,
+                    &settings.active_profile,
+                );
+                return Err(explain_node_creation_error(e, &data_dir));
+            }
         };
-        let manager = Arc::new(StorageManager::new(config).await?);
         STORAGE_MANAGER.set(manager.clone()).map_err(|_| {
             StorageError::Configuration("Failed to initialize Storage manager".to_string())
         })?;
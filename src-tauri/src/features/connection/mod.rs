@@ -1,7 +1,9 @@
 pub mod commands;
 pub mod config;
 pub mod connection;
+pub mod ports;
 
 pub use commands::*;
 pub use config::*;
 pub use connection::*;
+pub use ports::*;
@@ -0,0 +1,141 @@
+use std::net::{Ipv4Addr, TcpListener, UdpSocket};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::features::connection::{
+    create_codex_config_for_profile, get_storage_manager_with_handle,
+};
+use crate::features::settings::{load_settings, save_settings, NodeProfile};
+use crate::features::shared::{StorageConnectionStatus, StorageError};
+
+// How far above a taken port to look for a free one before asking the system
+const FALLBACK_RANGE: u16 = 100;
+
+#[derive(Debug, Clone, Copy)]
+enum Transport {
+    Tcp,
+    Udp,
+}
+
+fn port_available(port: u16, transport: Transport) -> bool {
+    match transport {
+        Transport::Tcp => TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok(),
+        Transport::Udp => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok(),
+    }
+}
+
+fn free_port_near(port: u16, transport: Transport) -> Result<u16, StorageError> {
+    let nearby = (1..=FALLBACK_RANGE)
+        .filter_map(|offset| port.checked_add(offset))
+        .find(|candidate| port_available(*candidate, transport));
+    if let Some(candidate) = nearby {
+        return Ok(candidate);
+    }
+
+    let address = match transport {
+        Transport::Tcp => {
+            TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).and_then(|l| l.local_addr())
+        }
+        Transport::Udp => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).and_then(|s| s.local_addr()),
+    };
+    address
+        .map(|address| address.port())
+        .map_err(|_| StorageError::PortInUse { port })
+}
+
+/// Ports of the profile that another process is already bound to
+fn conflicting_ports(profile: &NodeProfile) -> Vec<(u16, Transport)> {
+    let mut ports = vec![(profile.discovery_port, Transport::Udp)];
+    // Custom listen addresses carry their own ports and are left to the node
+    if profile.listen_port != 0 && profile.listen_addrs.is_empty() {
+        ports.push((profile.listen_port, Transport::Tcp));
+    }
+    ports
+        .into_iter()
+        .filter(|(port, transport)| !port_available(*port, *transport))
+        .collect()
+}
+
+/// Moves every taken port of the profile to a free one
+fn reassign_ports(
+    mut profile: NodeProfile,
+    conflicts: Vec<(u16, Transport)>,
+) -> Result<NodeProfile, StorageError> {
+    for (port, transport) in conflicts {
+        let replacement = free_port_near(port, transport)?;
+        warn!(
+            "Port {} is in use, profile '{}' uses {} instead",
+            port, profile.name, replacement
+        );
+        match transport {
+            Transport::Udp => profile.discovery_port = replacement,
+            Transport::Tcp => profile.listen_port = replacement,
+        }
+    }
+    Ok(profile)
+}
+
+/// Reloads the node with the ports of `profile`
+async fn apply_ports(profile: &NodeProfile, app_handle: &AppHandle) -> Result<(), StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    manager
+        .switch_config(create_codex_config_for_profile(app_handle, profile)?)
+        .await
+}
+
+/// Stores the ports of `profile` and reloads the node with them
+async fn save_ports(profile: &NodeProfile, app_handle: &AppHandle) -> Result<(), StorageError> {
+    let mut settings = load_settings(app_handle);
+    match settings
+        .profiles
+        .iter_mut()
+        .find(|existing| existing.name == profile.name)
+    {
+        Some(existing) => *existing = profile.clone(),
+        None => settings.profiles.push(profile.clone()),
+    }
+    save_settings(app_handle, &settings)?;
+    apply_ports(profile, app_handle).await
+}
+
+/// Checks that the ports of the active profile are free before the node starts.
+///
+/// A taken port is replaced by a free one for this run when the profile allows it,
+/// the stored profile keeps its ports. Otherwise `PortInUse` is returned so the user
+/// can pick a port or call `resolve_port_conflicts_with_handle`.
+pub async fn ensure_ports_available(app_handle: &AppHandle) -> Result<(), StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    // A running node holds its own ports
+    if manager.get_status().await == StorageConnectionStatus::Connected {
+        return Ok(());
+    }
+
+    let profile = load_settings(app_handle).active_profile();
+    let conflicts = conflicting_ports(&profile);
+    match conflicts.first() {
+        None => Ok(()),
+        Some((port, _)) if !profile.port_fallback => Err(StorageError::PortInUse { port: *port }),
+        Some(_) => apply_ports(&reassign_ports(profile, conflicts)?, app_handle).await,
+    }
+}
+
+/// Moves the taken ports of the active profile to free ones and saves them, whatever
+/// its fallback setting
+pub async fn resolve_port_conflicts_with_handle(
+    app_handle: AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    if manager.get_status().await == StorageConnectionStatus::Connected {
+        return Err(StorageError::NodeRunning);
+    }
+
+    let profile = load_settings(&app_handle).active_profile();
+    let conflicts = conflicting_ports(&profile);
+    if conflicts.is_empty() {
+        info!("No port conflicts for profile '{}'", profile.name);
+        return Ok(profile);
+    }
+    let profile = reassign_ports(profile, conflicts)?;
+    save_ports(&profile, &app_handle).await?;
+    Ok(profile)
+}
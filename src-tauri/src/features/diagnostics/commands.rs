@@ -0,0 +1,15 @@
+use crate::features::diagnostics::export_diagnostics_with_handle;
+use crate::features::shared::map_storage_error;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// Returns the names of the files written into the bundle
+#[tauri::command]
+pub async fn export_diagnostics(
+    save_path: String,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, String> {
+    export_diagnostics_with_handle(PathBuf::from(save_path), app_handle)
+        .await
+        .map_err(map_storage_error)
+}
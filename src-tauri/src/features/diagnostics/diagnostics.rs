@@ -0,0 +1,335 @@
+use serde_json::json;
+use std::fs::File;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::logging::recent_logs;
+use crate::features::settings::load_settings;
+use crate::features::shared::StorageError;
+
+const REDACTED_PATH: &str = "<path>";
+const REDACTED_SECRET: &str = "<redacted>";
+const REDACTED_IP: &str = "<ip>";
+
+// Files below these belong to the user, everything after them is redacted
+const USER_DIRS: &[&str] = &["<home>", "<temp>"];
+
+// Share keys and similar secrets are at least this long
+const MIN_SECRET_LEN: usize = 16;
+
+/// Replaces known directories with placeholders, then every remaining absolute path,
+/// public IP address and signed peer record
+struct Scrubber {
+    known_dirs: Vec<(String, &'static str)>,
+}
+
+impl Scrubber {
+    fn new(app_handle: &AppHandle) -> Self {
+        let path = app_handle.path();
+        let mut known_dirs: Vec<(String, &'static str)> = [
+            (path.app_data_dir().ok(), "<app_data>"),
+            (path.app_local_data_dir().ok(), "<app_local_data>"),
+            (path.app_cache_dir().ok(), "<app_cache>"),
+            (path.app_log_dir().ok(), "<app_log>"),
+            (dirs::home_dir(), "<home>"),
+            (Some(std::env::temp_dir()), "<temp>"),
+        ]
+        .into_iter()
+        .filter_map(|(dir, placeholder)| dir.map(|dir| (dir, placeholder)))
+        .flat_map(|(dir, placeholder)| {
+            let raw = dir.to_string_lossy().to_string();
+            // Debug output escapes Windows separators
+            let escaped = raw.replace('\\', "\\\\");
+            [(escaped, placeholder), (raw, placeholder)]
+        })
+        .filter(|(dir, _)| dir.len() > 1)
+        .collect();
+
+        // Replace the most specific directories first
+        known_dirs.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        known_dirs.dedup_by(|a, b| a.0 == b.0);
+        Self { known_dirs }
+    }
+
+    fn scrub(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (dir, placeholder) in &self.known_dirs {
+            text = text.replace(dir.as_str(), placeholder);
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(c) = rest.chars().next() {
+            if is_separator(c) {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+
+            let word_end = rest.find(is_separator).unwrap_or(rest.len());
+            let word = &rest[..word_end];
+            if is_absolute_path(word) {
+                out.push_str(REDACTED_PATH);
+                rest = &rest[path_end(rest)..];
+            } else if let Some(dir) = user_dir(word) {
+                out.push_str(&format!("{}/{}", dir, REDACTED_PATH));
+                rest = &rest[path_end(rest)..];
+            } else {
+                out.push_str(&scrub_word(word));
+                rest = &rest[word_end..];
+            }
+        }
+        out
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '(' | ')' | '[' | ']')
+}
+
+/// Paths may contain spaces, so one runs up to the next quote, line break or ": "
+fn path_end(text: &str) -> usize {
+    let line_end = text.find(['"', '\n', '\r', '\t']).unwrap_or(text.len());
+    let end = text[..line_end].find(": ").unwrap_or(line_end);
+    text[..end].trim_end().len()
+}
+
+/// The placeholder of a user directory `word` points below
+fn user_dir(word: &str) -> Option<&'static str> {
+    USER_DIRS.iter().copied().find(|dir| {
+        word.strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with(['/', '\\']))
+    })
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ip(&IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            let unique_local = first & 0xfe00 == 0xfc00;
+            let link_local = first & 0xffc0 == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+/// Redacts `word` if it is a public address, with or without a port
+fn scrub_ip(word: &str) -> Option<String> {
+    let address = word.trim_end_matches(['.', ';']);
+    let suffix = &word[address.len()..];
+    if let Ok(ip) = address.parse::<IpAddr>() {
+        return is_public_ip(&ip).then(|| format!("{}{}", REDACTED_IP, suffix));
+    }
+    let socket = address.parse::<SocketAddr>().ok()?;
+    is_public_ip(&socket.ip()).then(|| format!("{}:{}{}", REDACTED_IP, socket.port(), suffix))
+}
+
+/// Redacts the public addresses of a multiaddr, keeping protocols and ports
+fn scrub_multiaddr(word: &str) -> String {
+    let mut parts: Vec<String> = word.split('/').map(str::to_string).collect();
+    for i in 1..parts.len() {
+        let is_ip = matches!(parts[i - 1].as_str(), "ip4" | "ip6");
+        if is_ip && parts[i].parse::<IpAddr>().is_ok_and(|ip| is_public_ip(&ip)) {
+            parts[i] = REDACTED_IP.to_string();
+        }
+    }
+    parts.join("/")
+}
+
+fn is_multiaddr(word: &str) -> bool {
+    ["/ip4/", "/ip6/", "/dns/", "/dns4/", "/dns6/", "/p2p/"]
+        .iter()
+        .any(|prefix| word.starts_with(prefix))
+}
+
+fn is_absolute_path(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    let unix = word.starts_with('/') && word[1..].contains('/') && !is_multiaddr(word);
+    let windows = bytes.len() > 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+    unix || windows
+}
+
+fn scrub_word(word: &str) -> String {
+    // Signed peer records carry the node's addresses
+    if let Some(record) = word.strip_prefix("spr:") {
+        if !record.is_empty() {
+            return format!("spr:{}", REDACTED_SECRET);
+        }
+    }
+    if is_multiaddr(word) {
+        return scrub_multiaddr(word);
+    }
+    if let Some(redacted) = scrub_ip(word) {
+        return redacted;
+    }
+
+    // Shares carry their key after a '#'
+    match word.split_once('#') {
+        Some((before, secret))
+            if secret.len() >= MIN_SECRET_LEN
+                && secret
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            format!("{}#{}", before, REDACTED_SECRET)
+        }
+        _ => word.to_string(),
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value)
+        .unwrap_or_else(|e| json!({ "error": e.to_string() }).to_string())
+}
+
+/// Writes a zip with everything needed for a bug report to `save_path`.
+///
+/// Paths, public addresses and secrets are scrubbed from every file; the node key
+/// and passphrases are never read.
+pub async fn export_diagnostics_with_handle(
+    save_path: PathBuf,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, StorageError> {
+    let scrubber = Scrubber::new(&app_handle);
+    let package = app_handle.package_info();
+    let mut files: Vec<(&str, String)> = Vec::new();
+
+    files.push((
+        "system.json",
+        to_json(&json!({
+            "app_name": package.name,
+            "app_version": package.version.to_string(),
+            "tauri_version": tauri::VERSION,
+            "os": std::env::consts::OS,
+            "os_family": std::env::consts::FAMILY,
+            "arch": std::env::consts::ARCH,
+            "generated_at": SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+        })),
+    ));
+
+    let logs = recent_logs(None, None)
+        .into_iter()
+        .map(|entry| {
+            format!(
+                "{} {:?} {}: {}",
+                entry.timestamp, entry.level, entry.target, entry.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    files.push(("logs.txt", logs));
+
+    files.push(("settings.json", to_json(&load_settings(&app_handle))));
+
+    match get_storage_manager_with_handle(Some(app_handle.clone())).await {
+        Ok(manager) => {
+            files.push(("codex_config.txt", format!("{:#?}", manager.config().await)));
+            files.push(("status.json", to_json(&manager.get_status().await)));
+            let node_info = match manager.get_node_info().await {
+                Ok(info) => to_json(&info),
+                Err(e) => to_json(&json!({ "error": e.to_string() })),
+            };
+            files.push(("node_info.json", node_info));
+            files.push((
+                "operations.json",
+                to_json(&manager.operation_history().await),
+            ));
+        }
+        Err(e) => files.push(("status.json", to_json(&json!({ "error": e.to_string() })))),
+    }
+
+    let mut writer =
+        ZipWriter::new(File::create(&save_path).map_err(|e| StorageError::Io(e.to_string()))?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in &files {
+        writer
+            .start_file(*name, options)
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        writer
+            .write_all(scrubber.scrub(contents).as_bytes())
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+    writer
+        .finish()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    Ok(files
+        .into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrub(text: &str) -> String {
+        Scrubber {
+            known_dirs: vec![("/home/alice".to_string(), "<home>")],
+        }
+        .scrub(text)
+    }
+
+    #[test]
+    fn redacts_whole_paths_with_spaces() {
+        assert_eq!(
+            scrub(r#""path": "/srv/My Files/tax return.pdf""#),
+            r#""path": "<path>""#
+        );
+        assert_eq!(
+            scrub("Failed to read /srv/My Files/a b.txt: denied"),
+            "Failed to read <path>: denied"
+        );
+        assert_eq!(
+            scrub("Uploaded /home/alice/My Documents/notes.txt"),
+            "Uploaded <home>/<path>"
+        );
+    }
+
+    #[test]
+    fn redacts_public_addresses_only() {
+        assert_eq!(
+            scrub(r#"["/ip4/203.0.113.7/tcp/8070", "/ip4/192.168.1.2/udp/8090"]"#),
+            r#"["/ip4/<ip>/tcp/8070", "/ip4/192.168.1.2/udp/8090"]"#
+        );
+        assert_eq!(
+            scrub("Dialing 203.0.113.7:8070 and 127.0.0.1."),
+            "Dialing <ip>:8070 and 127.0.0.1."
+        );
+        assert_eq!(
+            scrub("/ip6/2001:db8::1/tcp/1 /ip6/fe80::1/tcp/1"),
+            "/ip6/<ip>/tcp/1 /ip6/fe80::1/tcp/1"
+        );
+    }
+
+    #[test]
+    fn redacts_records_and_share_keys() {
+        assert_eq!(scrub("spr:CiUIAhIhAiJv"), "spr:<redacted>");
+        assert_eq!(
+            scrub("zDvZRwzmAkhz#abcdefghijklmnop1234"),
+            "zDvZRwzmAkhz#<redacted>"
+        );
+    }
+}
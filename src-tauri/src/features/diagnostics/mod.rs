@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod diagnostics;
+
+pub use commands::*;
+pub use diagnostics::*;
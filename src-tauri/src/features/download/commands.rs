@@ -1,14 +1,49 @@
-use crate::features::download::download_file_with_progress;
-use crate::features::shared::map_storage_error;
+use crate::features::download::{
+    download_file_with_passphrase, download_range_with_progress,
+    download_shared_file_with_progress, get_cid_info_with_handle,
+};
+use crate::features::shared::{map_storage_error, CidInfo};
 use tauri::AppHandle;
 
 #[tauri::command]
 pub async fn download_file_from_storage(
     cid: String,
     save_path: String,
+    passphrase: Option<String>,
     app_handle: AppHandle,
 ) -> Result<crate::features::shared::DownloadResultResponse, String> {
-    download_file_with_progress(cid, save_path.into(), app_handle)
+    download_file_with_passphrase(cid, save_path.into(), passphrase, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn download_shared_file(
+    share: String,
+    save_path: String,
+    app_handle: AppHandle,
+) -> Result<crate::features::shared::DownloadResultResponse, String> {
+    download_shared_file_with_progress(share, save_path.into(), app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn download_range(
+    cid: String,
+    offset: usize,
+    length: usize,
+    save_path: String,
+    app_handle: AppHandle,
+) -> Result<crate::features::shared::DownloadResultResponse, String> {
+    download_range_with_progress(cid, offset, length, save_path.into(), app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn get_cid_info(cid: String, app_handle: AppHandle) -> Result<CidInfo, String> {
+    get_cid_info_with_handle(cid, app_handle)
         .await
         .map_err(map_storage_error)
 }
@@ -1,12 +1,67 @@
-use codex_bindings::{download_stream, DownloadStreamOptions};
-use std::path::PathBuf;
+use codex_bindings::{download_manifest, download_stream, CodexNode, DownloadStreamOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use uuid::Uuid;
 
-use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::compression::{
+    decompress_file, read_compressed_header, COMPRESSED_MIMETYPE, COMPRESSION_HEADER_LEN,
+};
+use crate::features::connection::{get_storage_manager_with_handle, StorageManager};
+use crate::features::encryption::{decrypt_file, parse_share, read_key_source, EncryptionSecret};
 use crate::features::shared::{
-    DownloadResultResponse, OperationStage, ProgressMessage, StorageError,
+    CidInfo, DownloadResultResponse, OperationStage, ProgressMessage, StorageError,
 };
 
+/// Fetches only the manifest of a CID and returns its metadata
+pub async fn fetch_cid_info(node: &CodexNode, cid: &str) -> Result<CidInfo, StorageError> {
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+
+    if cid.is_empty() {
+        return Err(StorageError::InvalidCid("CID cannot be empty".to_string()));
+    }
+
+    let manifest = download_manifest(node, cid)
+        .await
+        .map_err(|e| StorageError::Download(e.to_string()))?;
+
+    Ok(CidInfo {
+        cid: cid.to_string(),
+        filename: Some(manifest.filename).filter(|name| !name.is_empty()),
+        mimetype: Some(manifest.mimetype).filter(|mimetype| !mimetype.is_empty()),
+        dataset_size: manifest.dataset_size,
+        block_size: manifest.block_size,
+    })
+}
+
+pub async fn get_cid_info_with_handle(
+    cid: String,
+    app_handle: tauri::AppHandle,
+) -> Result<CidInfo, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle)).await?;
+    let node = manager.get_node().await?;
+    fetch_cid_info(&node, &cid).await
+}
+
+fn is_compressed(cid_info: &CidInfo) -> bool {
+    cid_info.mimetype.as_deref() == Some(COMPRESSED_MIMETYPE)
+}
+
+/// Reads the original size from the compression header once enough of the file has
+/// arrived. Encrypted content hides the header until it is decrypted.
+fn original_size_from_header(path: &Path, known: &OnceLock<Option<u64>>) -> Option<usize> {
+    if let Some(size) = known.get() {
+        return size.map(|size| size as usize);
+    }
+    let arrived = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if arrived < COMPRESSION_HEADER_LEN as u64 {
+        return None;
+    }
+    let size = read_compressed_header(path).ok().flatten();
+    known.get_or_init(|| size).map(|size| size as usize)
+}
+
 pub async fn download_file_with_progress(
     cid: String,
     save_path: PathBuf,
@@ -15,10 +70,20 @@ pub async fn download_file_with_progress(
     let manager = get_storage_manager_with_handle(Some(app_handle)).await?;
 
     let operation_id = Uuid::new_v4().to_string();
-    let cid_clone = cid.clone();
 
-    // Register progress sender
-    let _rx = manager.register_progress_sender(operation_id.clone()).await;
+    // The sender is unregistered whichever way this returns
+    let _progress = manager.track_progress(operation_id.clone()).await?;
+
+    run_download(manager.clone(), operation_id, cid, save_path).await
+}
+
+async fn run_download(
+    manager: Arc<StorageManager>,
+    operation_id: String,
+    cid: String,
+    save_path: PathBuf,
+) -> Result<DownloadResultResponse, StorageError> {
+    let cid_clone = cid.clone();
 
     // Send initial progress
     let initial_progress =
@@ -37,24 +102,44 @@ pub async fn download_file_with_progress(
         return Err(StorageError::InvalidCid("CID cannot be empty".to_string()));
     }
 
+    // Fetch the manifest first so progress can report an accurate total
+    let cid_info = fetch_cid_info(&node, &cid).await?;
+    let total_bytes = cid_info.dataset_size;
+    // Only content marked compressed in its manifest has a size header worth reading
+    let original_size = Arc::new(OnceLock::new());
+    if !is_compressed(&cid_info) {
+        let _ = original_size.set(None);
+    }
+
     // Send download start info
     let start_progress = ProgressMessage::new(operation_id.clone())
         .with_stage(OperationStage::Downloading)
-        .with_message(format!("Starting download of CID: {}", cid));
+        .with_bytes(0, Some(total_bytes))
+        .with_message(format!(
+            "Starting download of CID: {} ({} bytes)",
+            cid, total_bytes
+        ));
     manager.send_progress(&operation_id, start_progress).await;
 
     // Create download options with progress callback
     let operation_id_clone = operation_id.clone();
     let manager_clone = manager.clone();
+    let original_size_clone = original_size.clone();
+    let save_path_clone = save_path.clone();
     let download_options = DownloadStreamOptions::new(&cid)
         .filepath(&save_path)
         .on_progress(move |progress| {
             let manager = manager_clone.clone();
             let operation_id_for_callback = operation_id_clone.clone();
+            let original_bytes = original_size_from_header(&save_path_clone, &original_size_clone);
             tokio::spawn(async move {
                 let progress_msg = ProgressMessage::new(operation_id_for_callback.clone())
                     .with_stage(OperationStage::Downloading)
-                    .with_bytes(progress.bytes_downloaded, progress.total_bytes)
+                    .with_bytes(
+                        progress.bytes_downloaded,
+                        progress.total_bytes.or(Some(total_bytes)),
+                    )
+                    .with_original_bytes(original_bytes)
                     .with_message(format!("Downloaded {} bytes", progress.bytes_downloaded));
                 manager
                     .send_progress(&operation_id_for_callback, progress_msg)
@@ -71,19 +156,111 @@ pub async fn download_file_with_progress(
     let completion_progress = ProgressMessage::new(operation_id.clone())
         .with_stage(OperationStage::Completed)
         .with_bytes(result.size, Some(result.size))
+        .with_original_bytes(original_size_from_header(&save_path, &original_size))
+        .with_cid(cid.clone())
         .with_message("Download completed successfully".to_string());
     manager
         .send_progress(&operation_id, completion_progress)
         .await;
 
-    // Clean up progress sender
-    manager.unregister_progress_sender(&operation_id).await;
-
     Ok(DownloadResultResponse {
         cid: cid_clone,
         size: result.size,
+        stored_size: result.size,
         duration_ms: 0, // TODO: Track actual duration
         verified: true,
         filepath: Some(save_path.to_string_lossy().to_string()),
     })
 }
+
+/// Downloads the CID referenced by a share string and decrypts it into `save_path`
+pub async fn download_shared_file_with_progress(
+    share: String,
+    save_path: PathBuf,
+    app_handle: tauri::AppHandle,
+) -> Result<DownloadResultResponse, StorageError> {
+    let (cid, key) = parse_share(&share)?;
+    download_and_decode(cid, save_path, Some(EncryptionSecret::Key(key)), app_handle).await
+}
+
+/// Downloads a CID and decrypts it with the given passphrase when it is protected
+pub async fn download_file_with_passphrase(
+    cid: String,
+    save_path: PathBuf,
+    passphrase: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<DownloadResultResponse, StorageError> {
+    download_and_decode(
+        cid,
+        save_path,
+        passphrase.map(EncryptionSecret::Passphrase),
+        app_handle,
+    )
+    .await
+}
+
+/// Downloads a CID next to `save_path`, then reverses any storeman encoding found in the
+/// content header before moving the result into place
+async fn download_and_decode(
+    cid: String,
+    save_path: PathBuf,
+    secret: Option<EncryptionSecret>,
+    app_handle: tauri::AppHandle,
+) -> Result<DownloadResultResponse, StorageError> {
+    // Keep the raw content next to the destination so it never leaves the allowed scope
+    let temp_path = save_path.with_file_name(format!(
+        ".{}.{}.part",
+        save_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        Uuid::new_v4()
+    ));
+
+    let decrypted_path = temp_path.with_extension("dec");
+
+    let result = async {
+        let compressed =
+            is_compressed(&get_cid_info_with_handle(cid.clone(), app_handle.clone()).await?);
+        let download = download_file_with_progress(cid, temp_path.clone(), app_handle).await?;
+
+        let decode_src = temp_path.clone();
+        let decode_decrypted = decrypted_path.clone();
+        let decode_dst = save_path.clone();
+        let size = tokio::task::spawn_blocking(move || -> Result<u64, StorageError> {
+            let mut current = decode_src;
+
+            // Undo the upload transforms in reverse order: decrypt, then decompress
+            if read_key_source(&current)?.is_some() {
+                let secret = secret.ok_or(StorageError::PassphraseRequired)?;
+                decrypt_file(&current, &decode_decrypted, &secret)?;
+                current = decode_decrypted;
+            }
+
+            // The user already confirmed overwriting in the save dialog
+            let _ = std::fs::remove_file(&decode_dst);
+
+            if compressed {
+                return decompress_file(&current, &decode_dst);
+            }
+
+            std::fs::rename(&current, &decode_dst).map_err(|e| StorageError::Io(e.to_string()))?;
+            std::fs::metadata(&decode_dst)
+                .map(|metadata| metadata.len())
+                .map_err(|e| StorageError::Io(e.to_string()))
+        })
+        .await
+        .map_err(|e| StorageError::Download(e.to_string()))??;
+
+        Ok(DownloadResultResponse {
+            size: size as usize,
+            filepath: Some(save_path.to_string_lossy().to_string()),
+            ..download
+        })
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    let _ = tokio::fs::remove_file(&decrypted_path).await;
+    result
+}
@@ -1,5 +1,7 @@
 pub mod commands;
 pub mod download;
+pub mod range;
 
 pub use commands::*;
 pub use download::*;
+pub use range::*;
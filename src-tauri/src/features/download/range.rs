@@ -0,0 +1,148 @@
+use codex_bindings::{download_cancel, download_chunk, download_init, DownloadOptions};
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::download::fetch_cid_info;
+use crate::features::shared::{
+    DownloadResultResponse, OperationStage, ProgressMessage, StorageError,
+};
+
+/// Downloads `length` bytes starting at `offset` into `save_path`.
+///
+/// Only the blocks covering the range are fetched: the stream starts at the block
+/// holding `offset` and is cancelled as soon as the range has been received.
+pub async fn download_range_with_progress(
+    cid: String,
+    offset: usize,
+    length: usize,
+    save_path: PathBuf,
+    app_handle: tauri::AppHandle,
+) -> Result<DownloadResultResponse, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle)).await?;
+
+    let operation_id = Uuid::new_v4().to_string();
+    let start_time = std::time::Instant::now();
+
+    // The sender is unregistered whichever way this returns
+    let _progress = manager.track_progress(operation_id.clone()).await?;
+
+    // Send initial progress
+    let initial_progress =
+        ProgressMessage::new(operation_id.clone()).with_stage(OperationStage::Initializing);
+    manager.send_progress(&operation_id, initial_progress).await;
+
+    // Get the node
+    let node = manager.get_node().await?;
+
+    let cid_info = fetch_cid_info(&node, &cid).await?;
+
+    if length == 0 || offset >= cid_info.dataset_size {
+        return Err(StorageError::Download(format!(
+            "Range {}+{} is outside of the {} byte dataset",
+            offset, length, cid_info.dataset_size
+        )));
+    }
+
+    // Clamp the range to the end of the dataset
+    let length = length.min(cid_info.dataset_size - offset);
+    let end = offset + length;
+    let block_size = cid_info.block_size.max(1);
+    let first_block = offset / block_size;
+
+    let start_progress = ProgressMessage::new(operation_id.clone())
+        .with_stage(OperationStage::Downloading)
+        .with_bytes(0, Some(length))
+        .with_message(format!(
+            "Starting download of bytes {}-{} of CID: {}",
+            offset,
+            end - 1,
+            cid
+        ));
+    manager.send_progress(&operation_id, start_progress).await;
+
+    let mut file = tokio::fs::File::create(&save_path)
+        .await
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let download_options = DownloadOptions::new(&cid)
+        .chunk_size(block_size)
+        .start_block(first_block);
+    download_init(&node, &cid, &download_options)
+        .await
+        .map_err(|e| StorageError::Download(e.to_string()))?;
+
+    let result = async {
+        // Dataset offset of the next byte the stream delivers, chunks are not
+        // assumed to be exactly one block long
+        let mut position = first_block * block_size;
+        let mut written = 0;
+
+        while position < end {
+            let chunk = download_chunk(&node, &cid)
+                .await
+                .map_err(|e| StorageError::Download(e.to_string()))?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            // Trim the chunk to the requested range
+            let from = offset.saturating_sub(position).min(chunk.len());
+            let to = (end - position).min(chunk.len());
+            position += chunk.len();
+            if from >= to {
+                continue;
+            }
+
+            file.write_all(&chunk[from..to])
+                .await
+                .map_err(|e| StorageError::Io(e.to_string()))?;
+            written += to - from;
+
+            let progress_msg = ProgressMessage::new(operation_id.clone())
+                .with_stage(OperationStage::Downloading)
+                .with_bytes(written, Some(length))
+                .with_message(format!("Downloaded {} bytes", written));
+            manager.send_progress(&operation_id, progress_msg).await;
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+
+        Ok(written)
+    }
+    .await;
+
+    // Stop the stream once the range is covered, there is no need for the remaining blocks
+    let _ = download_cancel(&node, &cid).await;
+
+    let written = match result {
+        Ok(written) => written,
+        Err(e) => {
+            let failed_progress = ProgressMessage::new(operation_id.clone())
+                .with_stage(OperationStage::Failed(e.to_string()));
+            manager.send_progress(&operation_id, failed_progress).await;
+            return Err(e);
+        }
+    };
+
+    // Send completion progress
+    let completion_progress = ProgressMessage::new(operation_id.clone())
+        .with_stage(OperationStage::Completed)
+        .with_bytes(written, Some(length))
+        .with_message("Range download completed successfully".to_string());
+    manager
+        .send_progress(&operation_id, completion_progress)
+        .await;
+
+    Ok(DownloadResultResponse {
+        cid,
+        size: written,
+        stored_size: written,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        verified: written == length,
+        filepath: Some(save_path.to_string_lossy().to_string()),
+    })
+}
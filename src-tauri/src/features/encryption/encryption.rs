@@ -0,0 +1,659 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{OsRng, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::features::shared::StorageError;
+
+/// Marks content produced by [`encrypt_file`], followed by the format version
+pub const ENCRYPTION_MAGIC: &[u8; 5] = b"SMENC";
+
+/// Version of the header layout described on [`encrypt_file`]
+const FORMAT_VERSION: u8 = 1;
+
+pub const KEY_LEN: usize = 32;
+
+// XChaCha20 nonce minus the 5 bytes STREAM uses for its counter and last-chunk flag
+const NONCE_LEN: usize = 19;
+const TAG_LEN: usize = 16;
+const CHUNK_SIZE: usize = 64 * 1024;
+const SALT_LEN: usize = 16;
+const VERIFIER_LEN: usize = 16;
+const KDF_PARAMS_LEN: usize = 12;
+
+// Argon2id with the OWASP recommended minimum, pinned so a crate update cannot
+// change how existing files are derived
+const ARGON2_M_COST: u32 = 19 * 1024;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+// Refuse headers asking for more than 1 GiB of memory or absurd work before the
+// passphrase is even checked
+const MAX_ARGON2_M_COST: u32 = 1024 * 1024;
+const MAX_ARGON2_T_COST: u32 = 64;
+const MAX_ARGON2_P_COST: u32 = 16;
+
+/// Separates the CID from the key in a share string
+const SHARE_SEPARATOR: char = '#';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum KeySource {
+    Random = 0,
+    Passphrase = 1,
+}
+
+impl KeySource {
+    fn from_byte(byte: u8) -> Result<Self, StorageError> {
+        match byte {
+            0 => Ok(KeySource::Random),
+            1 => Ok(KeySource::Passphrase),
+            other => Err(StorageError::Decryption(format!(
+                "Unknown key source {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub fn to_base64(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.0)
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self, StorageError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded.trim())
+            .map_err(|e| StorageError::InvalidShare(e.to_string()))?;
+        let key: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| StorageError::InvalidShare(format!("Key must be {} bytes", KEY_LEN)))?;
+        Ok(Self(key))
+    }
+}
+
+/// What the user supplies to encrypt or decrypt content
+pub enum EncryptionSecret {
+    Key(EncryptionKey),
+    Passphrase(String),
+}
+
+/// Argon2id cost parameters, stored in the header of passphrase-encrypted files
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct KdfParams {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl KdfParams {
+    const PINNED: KdfParams = KdfParams {
+        m_cost: ARGON2_M_COST,
+        t_cost: ARGON2_T_COST,
+        p_cost: ARGON2_P_COST,
+    };
+
+    fn to_bytes(self) -> [u8; KDF_PARAMS_LEN] {
+        let mut bytes = [0u8; KDF_PARAMS_LEN];
+        bytes[..4].copy_from_slice(&self.m_cost.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.t_cost.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.p_cost.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; KDF_PARAMS_LEN]) -> Result<Self, StorageError> {
+        let field = |range: std::ops::Range<usize>| {
+            u32::from_le_bytes(bytes[range].try_into().unwrap_or_default())
+        };
+        let params = KdfParams {
+            m_cost: field(0..4),
+            t_cost: field(4..8),
+            p_cost: field(8..12),
+        };
+        if params.m_cost > MAX_ARGON2_M_COST
+            || params.t_cost > MAX_ARGON2_T_COST
+            || params.p_cost > MAX_ARGON2_P_COST
+        {
+            return Err(StorageError::Decryption(format!(
+                "Key derivation parameters out of range: {:?}",
+                params
+            )));
+        }
+        Ok(params)
+    }
+}
+
+/// Derives the content key and a verifier from a passphrase with Argon2id.
+///
+/// The verifier is stored in the header so a wrong passphrase can be told apart
+/// from corrupted ciphertext before any chunk is decrypted.
+fn derive_passphrase_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: KdfParams,
+) -> Result<(EncryptionKey, [u8; VERIFIER_LEN]), StorageError> {
+    let invalid =
+        |e: argon2::Error| StorageError::Configuration(format!("Key derivation failed: {}", e));
+    let argon2_params = Params::new(
+        params.m_cost,
+        params.t_cost,
+        params.p_cost,
+        Some(KEY_LEN + VERIFIER_LEN),
+    )
+    .map_err(invalid)?;
+
+    let mut output = [0u8; KEY_LEN + VERIFIER_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut output)
+        .map_err(invalid)?;
+
+    let mut key = [0u8; KEY_LEN];
+    let mut verifier = [0u8; VERIFIER_LEN];
+    key.copy_from_slice(&output[..KEY_LEN]);
+    verifier.copy_from_slice(&output[KEY_LEN..]);
+    Ok((EncryptionKey(key), verifier))
+}
+
+/// Builds the share string handed out for an encrypted upload
+pub fn build_share(cid: &str, key: &EncryptionKey) -> String {
+    format!("{}{}{}", cid, SHARE_SEPARATOR, key.to_base64())
+}
+
+/// Splits a share string back into its CID and key
+pub fn parse_share(share: &str) -> Result<(String, EncryptionKey), StorageError> {
+    let (cid, key) = share.trim().split_once(SHARE_SEPARATOR).ok_or_else(|| {
+        StorageError::InvalidShare("Share is missing the decryption key".to_string())
+    })?;
+
+    if cid.is_empty() {
+        return Err(StorageError::InvalidCid("CID cannot be empty".to_string()));
+    }
+
+    Ok((cid.to_string(), EncryptionKey::from_base64(key)?))
+}
+
+/// Returns how the file was encrypted, or `None` if it does not carry the encryption header
+pub fn read_key_source(path: &Path) -> Result<Option<KeySource>, StorageError> {
+    let mut file = File::open(path).map_err(|e| StorageError::Io(e.to_string()))?;
+    let mut header = [0u8; ENCRYPTION_MAGIC.len() + 2];
+    match file.read_exact(&mut header) {
+        Ok(()) if &header[..ENCRYPTION_MAGIC.len()] == ENCRYPTION_MAGIC => {
+            KeySource::from_byte(header[ENCRYPTION_MAGIC.len() + 1]).map(Some)
+        }
+        Ok(()) => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(StorageError::Io(e.to_string())),
+    }
+}
+
+/// Encrypts `src` into `dst` in fixed-size chunks, returning the ciphertext size.
+///
+/// Layout: magic | version | key source | [salt | kdf params | verifier] | nonce | chunks.
+/// The salt, Argon2 parameters and verifier are only present for passphrase-derived keys.
+/// Every chunk carries its own tag and authenticates the header, so reordering,
+/// truncation or header edits are rejected.
+pub fn encrypt_file(
+    src: &Path,
+    dst: &Path,
+    secret: &EncryptionSecret,
+) -> Result<u64, StorageError> {
+    let mut header = Vec::with_capacity(
+        ENCRYPTION_MAGIC.len() + 2 + SALT_LEN + KDF_PARAMS_LEN + VERIFIER_LEN + NONCE_LEN,
+    );
+    header.extend_from_slice(ENCRYPTION_MAGIC);
+    header.push(FORMAT_VERSION);
+
+    let key = match secret {
+        EncryptionSecret::Key(key) => {
+            header.push(KeySource::Random as u8);
+            key.clone()
+        }
+        EncryptionSecret::Passphrase(passphrase) => {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let (key, verifier) = derive_passphrase_key(passphrase, &salt, KdfParams::PINNED)?;
+            header.push(KeySource::Passphrase as u8);
+            header.extend_from_slice(&salt);
+            header.extend_from_slice(&KdfParams::PINNED.to_bytes());
+            header.extend_from_slice(&verifier);
+            key
+        }
+    };
+
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    header.extend_from_slice(&nonce);
+
+    let plaintext_len = std::fs::metadata(src)
+        .map_err(|e| StorageError::Io(e.to_string()))?
+        .len() as usize;
+    let chunk_count = plaintext_len.div_ceil(CHUNK_SIZE).max(1);
+
+    let mut reader = BufReader::new(File::open(src).map_err(|e| StorageError::Io(e.to_string()))?);
+    let mut writer =
+        BufWriter::new(File::create(dst).map_err(|e| StorageError::Io(e.to_string()))?);
+    writer
+        .write_all(&header)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let aead = XChaCha20Poly1305::new(GenericArray::from_slice(&key.0));
+    let mut encryptor = Some(EncryptorBE32::from_aead(
+        aead,
+        GenericArray::from_slice(&nonce),
+    ));
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut written = header.len() as u64;
+
+    for index in 0..chunk_count {
+        let len = CHUNK_SIZE.min(plaintext_len - index * CHUNK_SIZE);
+        reader
+            .read_exact(&mut buffer[..len])
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+
+        let payload = Payload {
+            msg: &buffer[..len],
+            aad: &header,
+        };
+        let ciphertext = if index + 1 == chunk_count {
+            encryptor.take().map(|e| e.encrypt_last(payload))
+        } else {
+            encryptor.as_mut().map(|e| e.encrypt_next(payload))
+        }
+        .ok_or_else(|| StorageError::Upload("Encryptor already finalized".to_string()))?
+        .map_err(|_| StorageError::Upload("Failed to encrypt chunk".to_string()))?;
+
+        writer
+            .write_all(&ciphertext)
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        written += ciphertext.len() as u64;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    Ok(written)
+}
+
+/// Decrypts a file produced by [`encrypt_file`], returning the plaintext size.
+///
+/// `dst` is removed again if any chunk fails authentication.
+pub fn decrypt_file(
+    src: &Path,
+    dst: &Path,
+    secret: &EncryptionSecret,
+) -> Result<u64, StorageError> {
+    let result = decrypt_file_inner(src, dst, secret);
+    if result.is_err() {
+        let _ = std::fs::remove_file(dst);
+    }
+    result
+}
+
+fn decrypt_file_inner(
+    src: &Path,
+    dst: &Path,
+    secret: &EncryptionSecret,
+) -> Result<u64, StorageError> {
+    let file_len = std::fs::metadata(src)
+        .map_err(|e| StorageError::Io(e.to_string()))?
+        .len() as usize;
+    let mut reader = BufReader::new(File::open(src).map_err(|e| StorageError::Io(e.to_string()))?);
+
+    let truncated = |_| StorageError::Decryption("Encryption header is truncated".to_string());
+
+    let mut header = vec![0u8; ENCRYPTION_MAGIC.len() + 2];
+    reader.read_exact(&mut header).map_err(truncated)?;
+    if &header[..ENCRYPTION_MAGIC.len()] != ENCRYPTION_MAGIC {
+        return Err(StorageError::Decryption(
+            "Content is not encrypted by storeman".to_string(),
+        ));
+    }
+    let version = header[ENCRYPTION_MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(StorageError::Decryption(format!(
+            "Unsupported encryption format version {}",
+            version
+        )));
+    }
+
+    let key = match (
+        KeySource::from_byte(header[ENCRYPTION_MAGIC.len() + 1])?,
+        secret,
+    ) {
+        (KeySource::Random, EncryptionSecret::Key(key)) => key.clone(),
+        (KeySource::Passphrase, EncryptionSecret::Passphrase(passphrase)) => {
+            let mut salt = [0u8; SALT_LEN];
+            reader.read_exact(&mut salt).map_err(truncated)?;
+            header.extend_from_slice(&salt);
+
+            let mut params = [0u8; KDF_PARAMS_LEN];
+            reader.read_exact(&mut params).map_err(truncated)?;
+            header.extend_from_slice(&params);
+            let params = KdfParams::from_bytes(&params)?;
+
+            let mut stored_verifier = [0u8; VERIFIER_LEN];
+            reader.read_exact(&mut stored_verifier).map_err(truncated)?;
+            header.extend_from_slice(&stored_verifier);
+
+            let (key, verifier) = derive_passphrase_key(passphrase, &salt, params)?;
+            if verifier != stored_verifier {
+                return Err(StorageError::WrongPassphrase);
+            }
+            key
+        }
+        (KeySource::Passphrase, EncryptionSecret::Key(_)) => {
+            return Err(StorageError::PassphraseRequired);
+        }
+        (KeySource::Random, EncryptionSecret::Passphrase(_)) => {
+            return Err(StorageError::InvalidShare(
+                "Content is encrypted with a random key, a share string is required".to_string(),
+            ));
+        }
+    };
+
+    let mut nonce = [0u8; NONCE_LEN];
+    reader.read_exact(&mut nonce).map_err(truncated)?;
+    header.extend_from_slice(&nonce);
+
+    let body_len = file_len - header.len();
+    let sealed_chunk = CHUNK_SIZE + TAG_LEN;
+    let chunk_count = body_len.div_ceil(sealed_chunk);
+    if chunk_count == 0 || body_len - (chunk_count - 1) * sealed_chunk < TAG_LEN {
+        return Err(StorageError::Decryption(
+            "Ciphertext is truncated".to_string(),
+        ));
+    }
+
+    let mut writer =
+        BufWriter::new(File::create(dst).map_err(|e| StorageError::Io(e.to_string()))?);
+
+    let aead = XChaCha20Poly1305::new(GenericArray::from_slice(&key.0));
+    let mut decryptor = Some(DecryptorBE32::from_aead(
+        aead,
+        GenericArray::from_slice(&nonce),
+    ));
+    let mut buffer = vec![0u8; sealed_chunk];
+    let mut written = 0u64;
+
+    for index in 0..chunk_count {
+        let len = sealed_chunk.min(body_len - index * sealed_chunk);
+        reader
+            .read_exact(&mut buffer[..len])
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+
+        let payload = Payload {
+            msg: &buffer[..len],
+            aad: &header,
+        };
+        let plaintext = if index + 1 == chunk_count {
+            decryptor.take().map(|d| d.decrypt_last(payload))
+        } else {
+            decryptor.as_mut().map(|d| d.decrypt_next(payload))
+        }
+        .ok_or_else(|| StorageError::Decryption("Decryptor already finalized".to_string()))?
+        .map_err(|_| {
+            StorageError::Decryption(format!(
+                "Chunk {} failed authentication, the content was tampered with or the key is wrong",
+                index
+            ))
+        })?;
+
+        writer
+            .write_all(&plaintext)
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        written += plaintext.len() as u64;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Temporary directory removed again when the test ends
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("storeman-enc-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self, name: &str, contents: &[u8]) -> PathBuf {
+            let path = self.0.join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    fn round_trip(plaintext: &[u8], secret: &EncryptionSecret) {
+        let scratch = Scratch::new();
+        let src = scratch.file("plain", plaintext);
+        let sealed = scratch.0.join("sealed");
+        let opened = scratch.0.join("opened");
+
+        let sealed_len = encrypt_file(&src, &sealed, secret).unwrap();
+        assert_eq!(sealed_len, std::fs::metadata(&sealed).unwrap().len());
+        let opened_len = decrypt_file(&sealed, &opened, secret).unwrap();
+
+        assert_eq!(opened_len, plaintext.len() as u64);
+        assert_eq!(std::fs::read(&opened).unwrap(), plaintext);
+    }
+
+    /// Encrypts `plaintext`, lets `tamper` edit the ciphertext and returns the decryption result
+    fn decrypt_tampered(
+        plaintext: &[u8],
+        secret: &EncryptionSecret,
+        tamper: impl FnOnce(&mut Vec<u8>),
+    ) -> Result<u64, StorageError> {
+        let scratch = Scratch::new();
+        let src = scratch.file("plain", plaintext);
+        let sealed = scratch.0.join("sealed");
+        encrypt_file(&src, &sealed, secret).unwrap();
+
+        let mut bytes = std::fs::read(&sealed).unwrap();
+        tamper(&mut bytes);
+        std::fs::write(&sealed, bytes).unwrap();
+
+        let opened = scratch.0.join("opened");
+        let result = decrypt_file(&sealed, &opened, secret);
+        if result.is_err() {
+            assert!(!opened.exists(), "partial plaintext must be removed");
+        }
+        result
+    }
+
+    fn key_secret() -> EncryptionSecret {
+        EncryptionSecret::Key(EncryptionKey::generate())
+    }
+
+    fn random_header_len() -> usize {
+        ENCRYPTION_MAGIC.len() + 2 + NONCE_LEN
+    }
+
+    #[test]
+    fn round_trips_with_random_key() {
+        for len in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE + 17,
+        ] {
+            round_trip(&sample(len), &key_secret());
+        }
+    }
+
+    #[test]
+    fn round_trips_with_passphrase() {
+        let secret = EncryptionSecret::Passphrase("correct horse battery staple".to_string());
+        round_trip(&sample(2 * CHUNK_SIZE + 5), &secret);
+        round_trip(&[], &secret);
+    }
+
+    #[test]
+    fn header_records_pinned_kdf_params() {
+        let scratch = Scratch::new();
+        let src = scratch.file("plain", b"hello");
+        let sealed = scratch.0.join("sealed");
+        encrypt_file(
+            &src,
+            &sealed,
+            &EncryptionSecret::Passphrase("pw".to_string()),
+        )
+        .unwrap();
+
+        let bytes = std::fs::read(&sealed).unwrap();
+        let start = ENCRYPTION_MAGIC.len() + 2 + SALT_LEN;
+        let stored: [u8; KDF_PARAMS_LEN] = bytes[start..start + KDF_PARAMS_LEN].try_into().unwrap();
+        assert_eq!(bytes[ENCRYPTION_MAGIC.len()], FORMAT_VERSION);
+        assert_eq!(KdfParams::from_bytes(&stored).unwrap(), KdfParams::PINNED);
+        assert_eq!(
+            read_key_source(&sealed).unwrap(),
+            Some(KeySource::Passphrase)
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase() {
+        let scratch = Scratch::new();
+        let src = scratch.file("plain", b"secret");
+        let sealed = scratch.0.join("sealed");
+        encrypt_file(
+            &src,
+            &sealed,
+            &EncryptionSecret::Passphrase("right".to_string()),
+        )
+        .unwrap();
+
+        let result = decrypt_file(
+            &sealed,
+            &scratch.0.join("opened"),
+            &EncryptionSecret::Passphrase("wrong".to_string()),
+        );
+        assert!(matches!(result, Err(StorageError::WrongPassphrase)));
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        let scratch = Scratch::new();
+        let src = scratch.file("plain", b"secret");
+        let sealed = scratch.0.join("sealed");
+        encrypt_file(&src, &sealed, &key_secret()).unwrap();
+
+        let result = decrypt_file(&sealed, &scratch.0.join("opened"), &key_secret());
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+    }
+
+    #[test]
+    fn rejects_flipped_ciphertext_byte() {
+        let secret = key_secret();
+        let result = decrypt_tampered(&sample(2 * CHUNK_SIZE), &secret, |bytes| {
+            let at = random_header_len() + CHUNK_SIZE + 100;
+            bytes[at] ^= 0x01;
+        });
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+    }
+
+    #[test]
+    fn rejects_header_edit() {
+        let secret = key_secret();
+        let result = decrypt_tampered(&sample(100), &secret, |bytes| {
+            bytes[random_header_len() - 1] ^= 0x80;
+        });
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+    }
+
+    #[test]
+    fn rejects_tampered_kdf_params() {
+        let secret = EncryptionSecret::Passphrase("pw".to_string());
+        let result = decrypt_tampered(b"hello", &secret, |bytes| {
+            bytes[ENCRYPTION_MAGIC.len() + 2 + SALT_LEN + 4] ^= 0x01;
+        });
+        assert!(matches!(result, Err(StorageError::WrongPassphrase)));
+    }
+
+    #[test]
+    fn rejects_truncation() {
+        let secret = key_secret();
+        // Dropping the whole last chunk leaves a stream without its final marker
+        let result = decrypt_tampered(&sample(2 * CHUNK_SIZE + 10), &secret, |bytes| {
+            bytes.truncate(random_header_len() + 2 * (CHUNK_SIZE + TAG_LEN));
+        });
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+
+        let result = decrypt_tampered(&sample(10), &secret, |bytes| {
+            bytes.truncate(bytes.len() - 1);
+        });
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+
+        let result = decrypt_tampered(&sample(10), &secret, |bytes| {
+            bytes.truncate(ENCRYPTION_MAGIC.len() + 3);
+        });
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+    }
+
+    #[test]
+    fn rejects_swapped_chunks() {
+        let secret = key_secret();
+        let result = decrypt_tampered(&sample(3 * CHUNK_SIZE), &secret, |bytes| {
+            let sealed = CHUNK_SIZE + TAG_LEN;
+            let first = random_header_len();
+            let (head, tail) = bytes[first..].split_at_mut(sealed);
+            head.swap_with_slice(&mut tail[..sealed]);
+        });
+        assert!(matches!(result, Err(StorageError::Decryption(_))));
+    }
+
+    #[test]
+    fn reports_key_source() {
+        let scratch = Scratch::new();
+        let src = scratch.file("plain", b"data");
+        let sealed = scratch.0.join("sealed");
+        encrypt_file(&src, &sealed, &key_secret()).unwrap();
+
+        assert_eq!(read_key_source(&sealed).unwrap(), Some(KeySource::Random));
+        assert_eq!(read_key_source(&src).unwrap(), None);
+    }
+
+    #[test]
+    fn share_round_trips() {
+        let key = EncryptionKey::generate();
+        let (cid, parsed) = parse_share(&build_share("zdj7Wexample", &key)).unwrap();
+        assert_eq!(cid, "zdj7Wexample");
+        assert_eq!(parsed.0, key.0);
+        assert!(parse_share("zdj7Wexample").is_err());
+    }
+}
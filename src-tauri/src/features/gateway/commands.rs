@@ -0,0 +1,25 @@
+use crate::features::gateway::{get_gateway_status, start_gateway_server, stop_gateway_server};
+use crate::features::settings::load_settings;
+use crate::features::shared::{map_storage_error, GatewayStatus};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn start_gateway(
+    port: Option<u16>,
+    app_handle: AppHandle,
+) -> Result<GatewayStatus, String> {
+    let port = port.unwrap_or_else(|| load_settings(&app_handle).gateway.port);
+    start_gateway_server(port, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn stop_gateway() -> Result<(), String> {
+    stop_gateway_server().await.map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn gateway_status() -> Result<GatewayStatus, String> {
+    Ok(get_gateway_status().await)
+}
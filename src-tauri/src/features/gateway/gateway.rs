@@ -0,0 +1,548 @@
+use axum::body::Body;
+use axum::extract::{DefaultBodyLimit, Path, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures_util::StreamExt;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{oneshot, Mutex};
+use tokio_util::io::ReaderStream;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::features::connection::{get_storage_manager_with_handle, transfers_paused};
+use crate::features::download::{
+    download_file_with_progress, download_range_with_progress, fetch_cid_info,
+};
+use crate::features::settings::GatewaySettings;
+use crate::features::shared::{GatewayStatus, StorageError};
+use crate::features::upload::upload_file_with_progress;
+
+// Largest slice served for a range request on a CID that is not cached yet
+const STREAM_CHUNK_LIMIT: u64 = 8 * 1024 * 1024;
+// Least recently served datasets are evicted once the cache grows past this
+const CACHE_SIZE_LIMIT: u64 = 2 * 1024 * 1024 * 1024;
+// Origins of the app's own webview, the only pages allowed to call the gateway
+const APP_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+    #[cfg(debug_assertions)]
+    "http://localhost:1420",
+];
+
+struct RunningGateway {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+// Global gateway instance, at most one server runs at a time
+static GATEWAY: Mutex<Option<RunningGateway>> = Mutex::const_new(None);
+
+#[derive(Clone)]
+struct GatewayState {
+    app_handle: AppHandle,
+    cache_dir: PathBuf,
+    port: u16,
+}
+
+pub async fn start_gateway_server(
+    port: u16,
+    app_handle: AppHandle,
+) -> Result<GatewayStatus, StorageError> {
+    let mut gateway = GATEWAY.lock().await;
+    if let Some(running) = gateway.as_ref() {
+        if running.port == port {
+            return Ok(GatewayStatus::running(port));
+        }
+        return Err(StorageError::Configuration(format!(
+            "Gateway is already running on port {}",
+            running.port
+        )));
+    }
+
+    let cache_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join("gateway");
+    tokio::fs::create_dir_all(&cache_dir)
+        .await
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    let cleanup_dir = cache_dir.clone();
+    tokio::task::spawn_blocking(move || {
+        remove_partial_files(&cleanup_dir);
+        prune_cache(&cleanup_dir, None);
+    })
+    .await
+    .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let state = GatewayState {
+        app_handle,
+        cache_dir,
+        port,
+    };
+
+    let router = Router::new()
+        .route("/cid/{cid}", get(get_cid))
+        .route("/upload", post(post_upload))
+        .layer(DefaultBodyLimit::disable())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            check_request_source,
+        ))
+        .with_state(state);
+
+    // Only ever bind to localhost, the gateway is not meant to be exposed
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| StorageError::Io(format!("Failed to bind gateway to {}: {}", addr, e)))?;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    tokio::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        if let Err(e) = server.await {
+            error!("Gateway server error: {}", e);
+        }
+    });
+
+    info!("Gateway listening on http://{}", addr);
+
+    *gateway = Some(RunningGateway {
+        port,
+        shutdown: shutdown_tx,
+    });
+
+    Ok(GatewayStatus::running(port))
+}
+
+pub async fn stop_gateway_server() -> Result<(), StorageError> {
+    let running = {
+        let mut gateway = GATEWAY.lock().await;
+        gateway.take()
+    };
+
+    if let Some(running) = running {
+        let _ = running.shutdown.send(());
+        info!("Gateway on port {} stopped", running.port);
+    }
+
+    Ok(())
+}
+
+pub async fn get_gateway_status() -> GatewayStatus {
+    let gateway = GATEWAY.lock().await;
+    match gateway.as_ref() {
+        Some(running) => GatewayStatus::running(running.port),
+        None => GatewayStatus::stopped(),
+    }
+}
+
+/// Starts, stops or restarts the gateway so it matches the given settings
+pub async fn apply_gateway_settings(
+    settings: &GatewaySettings,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let status = get_gateway_status().await;
+
+    if !settings.enabled {
+        return stop_gateway_server().await;
+    }
+
+    if status.running && status.port != Some(settings.port) {
+        stop_gateway_server().await?;
+    }
+
+    start_gateway_server(settings.port, app_handle).await?;
+    Ok(())
+}
+
+/// Whether `host` names the gateway itself, a page rebinding its own domain to
+/// localhost still sends that domain
+fn is_gateway_host(host: &str, port: u16) -> bool {
+    host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port)
+}
+
+/// Binding to localhost keeps other hosts out, but not web pages running in the
+/// user's browser. Requests must name the gateway as host and, when a browser sends
+/// an origin, come from the app itself.
+async fn check_request_source(
+    State(state): State<GatewayState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    let host_allowed = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .is_some_and(|host| is_gateway_host(host, state.port));
+    if !host_allowed {
+        return (StatusCode::FORBIDDEN, "Host not allowed").into_response();
+    }
+
+    let origin_allowed = headers.get(header::ORIGIN).is_none_or(|origin| {
+        origin
+            .to_str()
+            .is_ok_and(|origin| APP_ORIGINS.contains(&origin))
+    });
+    if !origin_allowed {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    next.run(request).await
+}
+
+async fn get_cid(
+    State(state): State<GatewayState>,
+    Path(cid): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    match serve_cid(&state, cid, &headers).await {
+        Ok(response) => response,
+        Err(e) => error_response(e),
+    }
+}
+
+async fn serve_cid(
+    state: &GatewayState,
+    cid: String,
+    headers: &HeaderMap,
+) -> Result<Response, StorageError> {
+    // The CID doubles as the cache file name, so keep it to plain multibase characters
+    if !cid.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(StorageError::InvalidCid(cid));
+    }
+
+    let manager = get_storage_manager_with_handle(Some(state.app_handle.clone())).await?;
+    let node = manager.get_node().await?;
+    let cid_info = fetch_cid_info(&node, &cid).await?;
+    let file_size = cid_info.dataset_size as u64;
+
+    let content_type = cid_info
+        .mimetype
+        .clone()
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let range = match headers.get(header::RANGE) {
+        Some(value) => match parse_range(value, file_size) {
+            Some(range) => Some(range),
+            None => {
+                return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{}", file_size))],
+                )
+                    .into_response());
+            }
+        },
+        None => None,
+    };
+
+    let cached_path = state.cache_dir.join(&cid);
+    // Cached datasets are still served, only new transfers wait
+    if transfers_paused() && !cached_path.exists() {
+        return Err(StorageError::TransfersPaused);
+    }
+
+    // Players seek with range requests, so serve those from the network without
+    // pulling the whole dataset into the cache first
+    if let (false, Some((start, end))) = (cached_path.exists(), range) {
+        let end = end.min(start + STREAM_CHUNK_LIMIT - 1);
+        let bytes = fetch_range(state, &cid, start, end).await?;
+        if bytes.is_empty() {
+            return Err(StorageError::Download(format!(
+                "No data received for bytes {}-{}",
+                start, end
+            )));
+        }
+
+        let mut response = Response::builder()
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, bytes.len())
+            .header(header::ACCEPT_RANGES, "bytes")
+            .header(
+                header::CONTENT_RANGE,
+                format!(
+                    "bytes {}-{}/{}",
+                    start,
+                    start + bytes.len() as u64 - 1,
+                    file_size
+                ),
+            );
+        if let Some(value) = content_disposition(&cid_info.filename) {
+            response = response.header(header::CONTENT_DISPOSITION, value);
+        }
+
+        return response
+            .body(Body::from(bytes))
+            .map_err(|e| StorageError::Configuration(e.to_string()));
+    }
+
+    let file_path = cached_file(state, &cid).await?;
+
+    let mut file = tokio::fs::File::open(&file_path)
+        .await
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let (status, start, length) = match range {
+        Some((start, end)) => (StatusCode::PARTIAL_CONTENT, start, end - start + 1),
+        None => (StatusCode::OK, 0, file_size),
+    };
+
+    file.seek(std::io::SeekFrom::Start(start))
+        .await
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    let body = Body::from_stream(ReaderStream::new(file.take(length)));
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, start + length - 1, file_size),
+        );
+    }
+
+    if let Some(value) = content_disposition(&cid_info.filename) {
+        response = response.header(header::CONTENT_DISPOSITION, value);
+    }
+
+    response
+        .body(body)
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+/// Returns the local copy of a CID, downloading it into the gateway cache if needed
+async fn cached_file(state: &GatewayState, cid: &str) -> Result<PathBuf, StorageError> {
+    let file_path = state.cache_dir.join(cid);
+    if file_path.exists() {
+        // The modification time tracks the last use for eviction
+        if let Ok(file) = std::fs::File::options().write(true).open(&file_path) {
+            let _ = file.set_modified(std::time::SystemTime::now());
+        }
+        return Ok(file_path);
+    }
+
+    // Download under a temporary name so concurrent requests never see a partial file
+    let temp_path = state
+        .cache_dir
+        .join(format!("{}.{}.part", cid, Uuid::new_v4()));
+    if let Err(e) =
+        download_file_with_progress(cid.to_string(), temp_path.clone(), state.app_handle.clone())
+            .await
+    {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(e);
+    }
+
+    tokio::fs::rename(&temp_path, &file_path)
+        .await
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let cache_dir = state.cache_dir.clone();
+    let keep = file_path.clone();
+    let _ = tokio::task::spawn_blocking(move || prune_cache(&cache_dir, Some(&keep))).await;
+
+    Ok(file_path)
+}
+
+fn is_partial(path: &std::path::Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("part") | Some("range")
+    )
+}
+
+/// Removes downloads and uploads a previous run left unfinished
+fn remove_partial_files(cache_dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if is_partial(&entry.path()) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Evicts the least recently used datasets until the cache fits `CACHE_SIZE_LIMIT`.
+///
+/// `keep` is the dataset that is about to be served and is never evicted.
+fn prune_cache(cache_dir: &std::path::Path, keep: Option<&std::path::Path>) {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    let mut files: Vec<(PathBuf, u64, std::time::SystemTime)> = entries
+        .flatten()
+        .filter(|entry| !is_partial(&entry.path()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            let used = metadata.modified().ok()?;
+            Some((entry.path(), metadata.len(), used))
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort_by_key(|(_, _, used)| *used);
+    for (path, size, _) in files {
+        if total <= CACHE_SIZE_LIMIT {
+            break;
+        }
+        if Some(path.as_path()) == keep {
+            continue;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= size;
+            info!("Evicted {} from the gateway cache", path.display());
+        }
+    }
+}
+
+/// Fetches the inclusive byte range `start..=end` of a CID without caching the dataset
+async fn fetch_range(
+    state: &GatewayState,
+    cid: &str,
+    start: u64,
+    end: u64,
+) -> Result<Vec<u8>, StorageError> {
+    let temp_path = state.cache_dir.join(format!(
+        "{}.{}-{}.{}.range",
+        cid,
+        start,
+        end,
+        Uuid::new_v4()
+    ));
+
+    let result = download_range_with_progress(
+        cid.to_string(),
+        start as usize,
+        (end - start + 1) as usize,
+        temp_path.clone(),
+        state.app_handle.clone(),
+    )
+    .await;
+
+    let bytes = match result {
+        Ok(_) => tokio::fs::read(&temp_path)
+            .await
+            .map_err(|e| StorageError::Io(e.to_string())),
+        Err(e) => Err(e),
+    };
+
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    bytes
+}
+
+fn content_disposition(filename: &Option<String>) -> Option<HeaderValue> {
+    let filename = filename.as_ref()?;
+    HeaderValue::from_str(&format!(
+        "inline; filename=\"{}\"",
+        filename.replace('"', "")
+    ))
+    .ok()
+}
+
+async fn post_upload(State(state): State<GatewayState>, body: Body) -> Response {
+    match receive_upload(&state, body).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+async fn receive_upload(
+    state: &GatewayState,
+    body: Body,
+) -> Result<crate::features::shared::UploadResultResponse, StorageError> {
+    if transfers_paused() {
+        return Err(StorageError::TransfersPaused);
+    }
+
+    let temp_path = state
+        .cache_dir
+        .join(format!("upload-{}.part", Uuid::new_v4()));
+
+    let result = async {
+        let mut file = tokio::fs::File::create(&temp_path)
+            .await
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+
+        let mut stream = body.into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| StorageError::Upload(e.to_string()))?;
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+
+        upload_file_with_progress(temp_path.clone(), state.app_handle.clone()).await
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    result
+}
+
+/// Parses a single `bytes=start-end` range, returning inclusive bounds
+fn parse_range(value: &HeaderValue, file_size: u64) -> Option<(u64, u64)> {
+    let spec = value.to_str().ok()?.strip_prefix("bytes=")?;
+    // Multiple ranges are not supported
+    if spec.contains(',') || file_size == 0 {
+        return None;
+    }
+
+    let (start, end) = spec.trim().split_once('-')?;
+    let (start, end) = if start.is_empty() {
+        // Suffix range: the last N bytes
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 {
+            return None;
+        }
+        (file_size.saturating_sub(suffix), file_size - 1)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() {
+            file_size - 1
+        } else {
+            end.parse::<u64>().ok()?.min(file_size - 1)
+        };
+        (start, end)
+    };
+
+    if start > end || start >= file_size {
+        return None;
+    }
+
+    Some((start, end))
+}
+
+fn error_response(err: StorageError) -> Response {
+    let status = match err {
+        StorageError::NodeNotInitialized
+        | StorageError::NodeNotStarted
+        | StorageError::TransfersPaused
+        | StorageError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+        StorageError::InvalidCid(_) => StatusCode::BAD_REQUEST,
+        StorageError::FileNotFound(_) => StatusCode::NOT_FOUND,
+        StorageError::Download(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, err.to_string()).into_response()
+}
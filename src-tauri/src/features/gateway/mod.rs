@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod gateway;
+
+pub use commands::*;
+pub use gateway::*;
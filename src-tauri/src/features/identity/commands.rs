@@ -0,0 +1,35 @@
+use crate::features::identity::{export_identity_with_handle, import_identity_with_handle};
+use crate::features::shared::{map_storage_error, IdentityArchiveInfo};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn export_identity(
+    save_path: String,
+    passphrase: String,
+    profile: Option<String>,
+    include_metadata: bool,
+    app_handle: AppHandle,
+) -> Result<IdentityArchiveInfo, String> {
+    export_identity_with_handle(
+        profile,
+        PathBuf::from(save_path),
+        passphrase,
+        include_metadata,
+        app_handle,
+    )
+    .await
+    .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn import_identity(
+    archive_path: String,
+    passphrase: String,
+    profile: String,
+    app_handle: AppHandle,
+) -> Result<IdentityArchiveInfo, String> {
+    import_identity_with_handle(PathBuf::from(archive_path), passphrase, profile, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
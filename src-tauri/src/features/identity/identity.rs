@@ -0,0 +1,424 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::error;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::features::connection::{
+    create_codex_config_for_profile, get_storage_manager_with_handle, profile_data_dir,
+};
+use crate::features::encryption::{decrypt_file, encrypt_file, EncryptionSecret};
+use crate::features::instance::LEVELDB_LOCK_FILE;
+use crate::features::profiles::{create_node_profile, validate_profile_name};
+use crate::features::settings::{load_settings, NodeProfile};
+use crate::features::shared::{IdentityArchiveInfo, StorageConnectionStatus, StorageError};
+
+const ARCHIVE_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "identity.json";
+
+/// Private key file the node keeps in its data directory
+pub const NODE_KEY_FILE: &str = "key";
+/// Repository metadata directory, manifests and block bookkeeping live here
+pub const REPO_METADATA_DIR: &str = "meta";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveManifest {
+    version: u32,
+    profile: String,
+    peer_id: Option<String>,
+    created_at: u64,
+    includes_metadata: bool,
+    files: usize,
+}
+
+impl From<ArchiveManifest> for IdentityArchiveInfo {
+    fn from(manifest: ArchiveManifest) -> Self {
+        Self {
+            profile: manifest.profile,
+            peer_id: manifest.peer_id,
+            created_at: manifest.created_at,
+            includes_metadata: manifest.includes_metadata,
+            files: manifest.files,
+        }
+    }
+}
+
+fn temp_file(app_handle: &AppHandle, extension: &str) -> Result<PathBuf, StorageError> {
+    let temp_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    std::fs::create_dir_all(&temp_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    Ok(temp_dir.join(format!("{}.{}", Uuid::new_v4(), extension)))
+}
+
+fn zip_error(e: zip::result::ZipError) -> StorageError {
+    StorageError::Io(format!("Identity archive error: {}", e))
+}
+
+fn add_file<W: Write + std::io::Seek>(
+    writer: &mut ZipWriter<W>,
+    name: &str,
+    path: &Path,
+) -> Result<(), StorageError> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    writer.start_file(name, options).map_err(zip_error)?;
+    let mut file = File::open(path).map_err(|e| StorageError::Io(e.to_string()))?;
+    std::io::copy(&mut file, writer).map_err(|e| StorageError::Io(e.to_string()))?;
+    Ok(())
+}
+
+fn add_dir<W: Write + std::io::Seek>(
+    writer: &mut ZipWriter<W>,
+    prefix: &str,
+    dir: &Path,
+    count: &mut usize,
+) -> Result<(), StorageError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    for entry in entries.flatten() {
+        // Opening a lock file would drop the lock this process's node holds on it
+        if entry.file_name() == LEVELDB_LOCK_FILE {
+            continue;
+        }
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            add_dir(writer, &name, &path, count)?;
+        } else if path.is_file() {
+            add_file(writer, &name, &path)?;
+            *count += 1;
+        }
+    }
+    Ok(())
+}
+
+fn write_archive(
+    data_dir: &Path,
+    zip_path: &Path,
+    mut manifest: ArchiveManifest,
+) -> Result<ArchiveManifest, StorageError> {
+    let mut writer =
+        ZipWriter::new(File::create(zip_path).map_err(|e| StorageError::Io(e.to_string()))?);
+
+    add_file(&mut writer, NODE_KEY_FILE, &data_dir.join(NODE_KEY_FILE))?;
+    let mut count = 1;
+
+    let metadata_dir = data_dir.join(REPO_METADATA_DIR);
+    if manifest.includes_metadata && metadata_dir.is_dir() {
+        add_dir(&mut writer, REPO_METADATA_DIR, &metadata_dir, &mut count)?;
+    }
+    manifest.files = count;
+
+    let contents = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    writer
+        .start_file(MANIFEST_ENTRY, SimpleFileOptions::default())
+        .map_err(zip_error)?;
+    writer
+        .write_all(&contents)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    writer.finish().map_err(zip_error)?;
+
+    Ok(manifest)
+}
+
+/// Validates the archive and restores it into `data_dir`.
+///
+/// Everything is extracted next to `data_dir` first, so a damaged entry leaves the
+/// profile untouched, and only then moved into place.
+fn restore_archive(zip_path: &Path, data_dir: &Path) -> Result<ArchiveManifest, StorageError> {
+    let staging_dir = data_dir.with_file_name("node_data.restoring");
+    if staging_dir.exists() {
+        std::fs::remove_dir_all(&staging_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+
+    let result = extract_archive(zip_path, &staging_dir)
+        .and_then(|manifest| install_restored(&staging_dir, data_dir, &manifest).map(|_| manifest));
+    let _ = std::fs::remove_dir_all(&staging_dir);
+    result
+}
+
+/// Moves the extracted key and metadata from `staging_dir` into `data_dir`. The old
+/// metadata is put back if the key cannot be replaced.
+fn install_restored(
+    staging_dir: &Path,
+    data_dir: &Path,
+    manifest: &ArchiveManifest,
+) -> Result<(), StorageError> {
+    std::fs::create_dir_all(data_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let metadata_dir = data_dir.join(REPO_METADATA_DIR);
+    let old_metadata_dir = data_dir.join(format!("{}.old", REPO_METADATA_DIR));
+    let staged_metadata = staging_dir.join(REPO_METADATA_DIR);
+    let replace_metadata = manifest.includes_metadata && staged_metadata.is_dir();
+    let had_metadata = metadata_dir.exists();
+
+    if replace_metadata {
+        if old_metadata_dir.exists() {
+            std::fs::remove_dir_all(&old_metadata_dir)
+                .map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+        if had_metadata {
+            std::fs::rename(&metadata_dir, &old_metadata_dir)
+                .map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+        if let Err(e) = std::fs::rename(&staged_metadata, &metadata_dir) {
+            if had_metadata {
+                let _ = std::fs::rename(&old_metadata_dir, &metadata_dir);
+            }
+            return Err(StorageError::Io(e.to_string()));
+        }
+    }
+
+    if let Err(e) = std::fs::rename(
+        staging_dir.join(NODE_KEY_FILE),
+        data_dir.join(NODE_KEY_FILE),
+    ) {
+        if replace_metadata {
+            let _ = std::fs::remove_dir_all(&metadata_dir);
+            if had_metadata {
+                let _ = std::fs::rename(&old_metadata_dir, &metadata_dir);
+            }
+        }
+        return Err(StorageError::Io(e.to_string()));
+    }
+
+    if replace_metadata && had_metadata {
+        let _ = std::fs::remove_dir_all(&old_metadata_dir);
+    }
+    Ok(())
+}
+
+/// Validates the archive and extracts the key and metadata into `staging_dir`
+fn extract_archive(zip_path: &Path, staging_dir: &Path) -> Result<ArchiveManifest, StorageError> {
+    let file = File::open(zip_path).map_err(|e| StorageError::Io(e.to_string()))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|_| StorageError::Decryption("Not a storeman identity archive".to_string()))?;
+
+    let manifest: ArchiveManifest = {
+        let mut entry = archive
+            .by_name(MANIFEST_ENTRY)
+            .map_err(|_| StorageError::Decryption("Identity manifest is missing".to_string()))?;
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        serde_json::from_slice(&contents)
+            .map_err(|e| StorageError::Decryption(format!("Invalid identity manifest: {}", e)))?
+    };
+    if manifest.version != ARCHIVE_VERSION {
+        return Err(StorageError::Decryption(format!(
+            "Unsupported identity archive version {}",
+            manifest.version
+        )));
+    }
+
+    let key_size = archive
+        .by_name(NODE_KEY_FILE)
+        .map_err(|_| StorageError::Decryption("Node key is missing from archive".to_string()))?
+        .size();
+    if key_size == 0 {
+        return Err(StorageError::Decryption(
+            "Node key in archive is empty".to_string(),
+        ));
+    }
+
+    std::fs::create_dir_all(staging_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        let relative = match entry.enclosed_name() {
+            Some(relative) => relative,
+            None => continue,
+        };
+        let is_key = relative == Path::new(NODE_KEY_FILE);
+        let is_metadata = manifest.includes_metadata && relative.starts_with(REPO_METADATA_DIR);
+        if entry.is_dir() || !(is_key || is_metadata) {
+            continue;
+        }
+
+        let target = staging_dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+        let mut out = File::create(&target).map_err(|e| StorageError::Io(e.to_string()))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| StorageError::Io(e.to_string()))?;
+
+        #[cfg(unix)]
+        if is_key {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Writes the node key of a profile, and optionally its repo metadata, into a
+/// passphrase-encrypted archive at `save_path`
+pub async fn export_identity_with_handle(
+    profile: Option<String>,
+    save_path: PathBuf,
+    passphrase: String,
+    include_metadata: bool,
+    app_handle: AppHandle,
+) -> Result<IdentityArchiveInfo, StorageError> {
+    if passphrase.is_empty() {
+        return Err(StorageError::PassphraseRequired);
+    }
+
+    let settings = load_settings(&app_handle);
+    let profile = match profile {
+        Some(profile) => {
+            validate_profile_name(&profile)?;
+            if !settings.profiles.iter().any(|known| known.name == profile) {
+                return Err(StorageError::Configuration(format!(
+                    "Profile '{}' does not exist",
+                    profile
+                )));
+            }
+            profile
+        }
+        None => settings.active_profile().name,
+    };
+    let is_active = profile == settings.active_profile().name;
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    // A running node keeps writing its metadata, a copy would not be consistent
+    if include_metadata
+        && is_active
+        && manager.get_status().await == StorageConnectionStatus::Connected
+    {
+        return Err(StorageError::NodeRunning);
+    }
+
+    let data_dir = profile_data_dir(&app_handle, &profile);
+    if !data_dir.join(NODE_KEY_FILE).is_file() {
+        return Err(StorageError::FileNotFound(
+            data_dir.join(NODE_KEY_FILE).to_string_lossy().to_string(),
+        ));
+    }
+
+    let peer_id = if is_active {
+        manager
+            .get_node()
+            .await
+            .ok()
+            .and_then(|node| node.peer_id().ok())
+    } else {
+        None
+    };
+
+    let manifest = ArchiveManifest {
+        version: ARCHIVE_VERSION,
+        profile,
+        peer_id,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        includes_metadata: include_metadata,
+        files: 0,
+    };
+
+    let zip_path = temp_file(&app_handle, "zip")?;
+    let archive_zip = zip_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let manifest = write_archive(&data_dir, &archive_zip, manifest)?;
+        encrypt_file(
+            &archive_zip,
+            &save_path,
+            &EncryptionSecret::Passphrase(passphrase),
+        )?;
+        Ok::<_, StorageError>(manifest)
+    })
+    .await
+    .map_err(|e| StorageError::Io(e.to_string()));
+
+    let _ = std::fs::remove_file(&zip_path);
+    Ok(result??.into())
+}
+
+/// Restores an identity archive into `profile`, creating the profile if needed.
+///
+/// Refuses to touch the active profile while its node is running, and releases its
+/// repository for the restore otherwise.
+pub async fn import_identity_with_handle(
+    archive_path: PathBuf,
+    passphrase: String,
+    profile: String,
+    app_handle: AppHandle,
+) -> Result<IdentityArchiveInfo, StorageError> {
+    validate_profile_name(&profile)?;
+    if !archive_path.is_file() {
+        return Err(StorageError::FileNotFound(
+            archive_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let settings = load_settings(&app_handle);
+    let is_active = settings.active_profile().name == profile;
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    if is_active && manager.get_status().await == StorageConnectionStatus::Connected {
+        return Err(StorageError::NodeRunning);
+    }
+    // An initialized node already has the repository open
+    if is_active {
+        manager.release_node().await?;
+    }
+
+    let data_dir = profile_data_dir(&app_handle, &profile);
+    let zip_path = temp_file(&app_handle, "zip")?;
+    let archive_zip = zip_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        decrypt_file(
+            &archive_path,
+            &archive_zip,
+            &EncryptionSecret::Passphrase(passphrase),
+        )?;
+        restore_archive(&archive_zip, &data_dir)
+    })
+    .await
+    .map_err(|e| StorageError::Io(e.to_string()));
+
+    let _ = std::fs::remove_file(&zip_path);
+    let manifest = match result {
+        Ok(Ok(manifest)) => manifest,
+        Ok(Err(e)) | Err(e) => {
+            // The profile was left as it was, bring its node back
+            if is_active {
+                if let Err(init_error) = manager.initialize_node().await {
+                    error!("Failed to reinitialize node after import: {}", init_error);
+                }
+            }
+            return Err(e);
+        }
+    };
+
+    if !settings
+        .profiles
+        .iter()
+        .any(|existing| existing.name == profile)
+    {
+        create_node_profile(
+            NodeProfile {
+                name: profile.clone(),
+                ..NodeProfile::default()
+            },
+            &app_handle,
+        )?;
+    }
+
+    // Reload the node so it picks up the restored key
+    if is_active {
+        let node_profile = load_settings(&app_handle).active_profile();
+        manager
+            .switch_config(create_codex_config_for_profile(&app_handle, &node_profile)?)
+            .await?;
+    }
+
+    Ok(manifest.into())
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod identity;
+
+pub use commands::*;
+pub use identity::*;
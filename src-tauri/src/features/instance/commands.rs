@@ -0,0 +1,17 @@
+use crate::features::instance::{clear_stale_repo_lock_with_handle, repo_lock_status_with_handle};
+use crate::features::shared::{map_storage_error, RepoLockStatus};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn get_repo_lock_status(app_handle: AppHandle) -> Result<RepoLockStatus, String> {
+    repo_lock_status_with_handle(app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn clear_stale_repo_lock(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    clear_stale_repo_lock_with_handle(app_handle)
+        .await
+        .map_err(map_storage_error)
+}
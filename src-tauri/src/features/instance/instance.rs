@@ -0,0 +1,287 @@
+use once_cell::sync::Lazy;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::features::connection::{
+    get_storage_manager_with_handle, profile_data_dir, StorageManager,
+};
+use crate::features::settings::load_settings;
+use crate::features::shared::{RepoLockStatus, SecondInstanceArgs, StorageError};
+
+pub const SECOND_INSTANCE_EVENT: &str = "second-instance";
+
+/// Held for the lifetime of the process so no other storeman opens the same data
+const INSTANCE_LOCK_FILE: &str = "storeman.lock";
+/// Lock file LevelDB keeps in every database directory, also after a clean shutdown
+pub const LEVELDB_LOCK_FILE: &str = "LOCK";
+/// Databases the node keeps in its data directory that may be backed by LevelDB
+const LEVELDB_DIRS: &[&str] = &["meta", "repo", "dht", "dht/providers"];
+
+static INSTANCE_LOCK: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(None));
+
+fn instance_lock_path(app_handle: &AppHandle) -> Result<PathBuf, StorageError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(INSTANCE_LOCK_FILE))
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+/// Locks the app data directory for this process.
+///
+/// Fails with `RepoLocked` while another storeman process holds the lock. A lock
+/// file left behind by a crashed process is not held by anyone and is taken over.
+pub fn acquire_instance_lock(app_handle: &AppHandle) -> Result<(), StorageError> {
+    let mut guard = INSTANCE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if guard.is_some() {
+        return Ok(());
+    }
+
+    let path = instance_lock_path(app_handle)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return Err(StorageError::RepoLocked(
+                path.parent().unwrap_or(&path).to_string_lossy().to_string(),
+            ))
+        }
+        Err(TryLockError::Error(e)) => return Err(StorageError::Io(e.to_string())),
+    }
+
+    let mut previous = String::new();
+    let _ = file.read_to_string(&mut previous);
+    if !previous.trim().is_empty() {
+        warn!(
+            "Previous storeman process {} did not shut down cleanly",
+            previous.trim()
+        );
+    }
+
+    file.set_len(0)
+        .and_then(|_| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| write!(file, "{}", std::process::id()))
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    *guard = Some(file);
+    Ok(())
+}
+
+/// Clears and releases the instance lock, marking a clean shutdown
+pub fn release_instance_lock() {
+    let mut guard = INSTANCE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = guard.take() {
+        let _ = file.set_len(0);
+        let _ = file.unlock();
+    }
+}
+
+/// Focuses the running window and hands it the arguments of a second launch,
+/// e.g. a CID opened from a link
+pub fn handle_second_instance(app_handle: &AppHandle, args: Vec<String>, cwd: String) {
+    info!("Second launch forwarded {} argument(s)", args.len());
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    // The first argument is the executable itself
+    let payload = SecondInstanceArgs {
+        args: args.into_iter().skip(1).collect(),
+        cwd,
+    };
+    let _ = app_handle.emit(SECOND_INSTANCE_EVENT, payload);
+}
+
+fn find_lock_files(data_dir: &Path) -> Vec<PathBuf> {
+    LEVELDB_DIRS
+        .iter()
+        .map(|dir| data_dir.join(dir).join(LEVELDB_LOCK_FILE))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Returns whether another process holds the LevelDB lock on `path`.
+///
+/// LevelDB takes POSIX record locks, which `flock` style locks do not see, so the
+/// lock is probed with `F_GETLK` without taking it. Closing the probe drops every
+/// record lock this process has on the file, so it must never run while this
+/// process has the repository open.
+#[cfg(unix)]
+fn lock_held(path: &Path) -> Result<bool, StorageError> {
+    use nix::fcntl::{fcntl, FcntlArg};
+    use nix::libc;
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    // SAFETY: `flock` is a plain C struct for which all zeroes is a valid value
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_WRLCK as libc::c_short;
+    lock.l_whence = libc::SEEK_SET as libc::c_short;
+    fcntl(&file, FcntlArg::F_GETLK(&mut lock)).map_err(|e| StorageError::Io(e.to_string()))?;
+    Ok(lock.l_type != libc::F_UNLCK as libc::c_short)
+}
+
+/// Returns whether some process holds the lock on `path`, the lock is released
+/// again right away when it could be taken. LevelDB uses `LockFileEx` here, the
+/// same lock `try_lock` takes.
+#[cfg(not(unix))]
+fn lock_held(path: &Path) -> Result<bool, StorageError> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = file.unlock();
+            Ok(false)
+        }
+        Err(TryLockError::WouldBlock) => Ok(true),
+        Err(TryLockError::Error(e)) => Err(StorageError::Io(e.to_string())),
+    }
+}
+
+/// Splits the lock files of a data directory into those nobody holds and those in use
+fn partition_lock_files(data_dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>), StorageError> {
+    let mut free = Vec::new();
+    let mut held = Vec::new();
+    for path in find_lock_files(data_dir) {
+        if lock_held(&path)? {
+            held.push(path);
+        } else {
+            free.push(path);
+        }
+    }
+    Ok((free, held))
+}
+
+/// Turns a node creation failure caused by a repository lock into `RepoLocked`
+/// when a process holds it, or `StaleRepoLock` when nobody does. Other errors are
+/// returned unchanged.
+pub fn explain_node_creation_error(error: StorageError, data_dir: &Path) -> StorageError {
+    match &error {
+        StorageError::NodeCreation(msg) if msg.to_lowercase().contains("lock") => {
+            match partition_lock_files(data_dir) {
+                Ok((_, held)) if !held.is_empty() => {
+                    StorageError::RepoLocked(data_dir.to_string_lossy().to_string())
+                }
+                Ok((free, _)) if !free.is_empty() => {
+                    StorageError::StaleRepoLock(data_dir.to_string_lossy().to_string())
+                }
+                _ => error,
+            }
+        }
+        _ => error,
+    }
+}
+
+fn display_paths(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Reports the repository lock files of the active profile, which of them a
+/// process holds, and whether opening the node fails on a lock nobody holds.
+///
+/// Refuses while this process has a node open.
+pub async fn repo_lock_status_with_handle(
+    app_handle: AppHandle,
+) -> Result<RepoLockStatus, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await;
+    // Probing the lock files would drop the locks of this process's own node
+    if let Ok(manager) = &manager {
+        if manager.get_node().await.is_ok() {
+            return Err(StorageError::NodeRunning);
+        }
+    }
+    let stale = matches!(manager, Err(StorageError::StaleRepoLock(_)));
+
+    let data_dir = profile_data_dir(&app_handle, &load_settings(&app_handle).active_profile);
+    let (free, held) = partition_lock_files(&data_dir)?;
+
+    let mut lock_files = display_paths(&free);
+    lock_files.extend(display_paths(&held));
+    Ok(RepoLockStatus {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        lock_files,
+        held_lock_files: display_paths(&held),
+        stale: stale && held.is_empty(),
+    })
+}
+
+/// Deletes stale LevelDB lock files of the active profile and opens the node again.
+///
+/// A lock file is only deleted when no process holds its lock, so nothing that
+/// uses it is touched. It also refuses while this process has a node open.
+pub async fn clear_stale_repo_lock_with_handle(
+    app_handle: AppHandle,
+) -> Result<Vec<String>, StorageError> {
+    acquire_instance_lock(&app_handle)?;
+
+    let manager = match get_storage_manager_with_handle(Some(app_handle.clone())).await {
+        Ok(manager) => Some(manager),
+        Err(StorageError::StaleRepoLock(_)) => None,
+        Err(e) => return Err(e),
+    };
+    if let Some(manager) = &manager {
+        if manager.get_node().await.is_ok() {
+            return Err(StorageError::NodeRunning);
+        }
+    }
+
+    let data_dir = profile_data_dir(&app_handle, &load_settings(&app_handle).active_profile);
+    let (lock_files, held) = partition_lock_files(&data_dir)?;
+    if !held.is_empty() {
+        return Err(StorageError::RepoLocked(
+            data_dir.to_string_lossy().to_string(),
+        ));
+    }
+    for path in &lock_files {
+        // Checked again right before deleting, the lock may have been taken meanwhile
+        if lock_held(path)? {
+            return Err(StorageError::RepoLocked(
+                data_dir.to_string_lossy().to_string(),
+            ));
+        }
+        std::fs::remove_file(path).map_err(|e| StorageError::Io(e.to_string()))?;
+        warn!("Removed stale repository lock {}", path.display());
+    }
+
+    // Without a manager the next call creates one, which opens the node again
+    match manager {
+        Some(manager) => reopen_node(&manager, &data_dir).await?,
+        None => {
+            get_storage_manager_with_handle(Some(app_handle)).await?;
+        }
+    }
+
+    Ok(display_paths(&lock_files))
+}
+
+async fn reopen_node(manager: &StorageManager, data_dir: &Path) -> Result<(), StorageError> {
+    manager
+        .initialize_node()
+        .await
+        .map_err(|e| explain_node_creation_error(e, data_dir))
+}
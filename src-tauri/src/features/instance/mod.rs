@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod instance;
+
+pub use commands::*;
+pub use instance::*;
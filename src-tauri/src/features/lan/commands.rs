@@ -0,0 +1,16 @@
+use crate::features::lan::{list_lan_peers, set_lan_mode_with_handle};
+use crate::features::settings::NodeProfile;
+use crate::features::shared::{map_storage_error, LanPeer};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn set_lan_mode(enabled: bool, app_handle: AppHandle) -> Result<NodeProfile, String> {
+    set_lan_mode_with_handle(enabled, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn get_lan_peers() -> Result<Vec<LanPeer>, String> {
+    Ok(list_lan_peers().await)
+}
@@ -0,0 +1,291 @@
+use codex_bindings::debug;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tokio::net::UdpSocket;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::features::connection::{
+    create_codex_config_for_profile, get_storage_manager_with_handle,
+};
+use crate::features::peers::is_peer_banned;
+use crate::features::settings::{load_settings, save_settings, NodeProfile};
+use crate::features::shared::{LanPeer, StorageError};
+
+/// UDP port storeman nodes announce themselves on. The node's own discovery
+/// port is taken by the DHT, so beacons use a fixed port of their own.
+pub const LAN_DISCOVERY_PORT: u16 = 8087;
+
+const BEACON_MAGIC: &str = "storeman-lan-v1";
+const BEACON_INTERVAL: Duration = Duration::from_secs(5);
+// Peers that stopped announcing are forgotten after this long
+const PEER_TIMEOUT: Duration = Duration::from_secs(60);
+// Do not redial a peer more often than this
+const REDIAL_INTERVAL: Duration = Duration::from_secs(60);
+// Beacons are not authenticated, so a flood of made up peers must not grow the map
+const MAX_LAN_PEERS: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Beacon {
+    magic: String,
+    peer_id: String,
+    addresses: Vec<String>,
+}
+
+struct DiscoveredPeer {
+    addresses: Vec<String>,
+    last_seen: Instant,
+    last_seen_unix: u64,
+    last_dial: Option<Instant>,
+    connected: bool,
+}
+
+static LAN_PEERS: Lazy<Mutex<HashMap<String, DiscoveredPeer>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static LAN_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Keeps the addresses on the host the beacon came from, rewriting wildcard listen
+/// addresses to it. Beacons are not authenticated, so anything else could point
+/// the node at an arbitrary host.
+fn reachable_addresses(addresses: &[String], sender: IpAddr) -> Vec<String> {
+    let protocol = match sender {
+        IpAddr::V4(_) => "ip4",
+        IpAddr::V6(_) => "ip6",
+    };
+
+    addresses
+        .iter()
+        .filter_map(|address| {
+            let mut parts = address.splitn(4, '/');
+            let (Some(""), Some(kind), Some(ip), rest) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return None;
+            };
+            let ip: IpAddr = ip.parse().ok()?;
+            if kind != protocol || !(ip.is_unspecified() || ip == sender) {
+                return None;
+            }
+            Some(match rest {
+                Some(rest) => format!("/{}/{}/{}", protocol, sender, rest),
+                None => format!("/{}/{}", protocol, sender),
+            })
+        })
+        .collect()
+}
+
+async fn local_beacon(app_handle: &AppHandle) -> Option<Beacon> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone()))
+        .await
+        .ok()?;
+    let node = manager.get_node().await.ok()?;
+    if !node.is_started() {
+        return None;
+    }
+
+    let info = debug(&node).await.ok()?;
+    Some(Beacon {
+        magic: BEACON_MAGIC.to_string(),
+        peer_id: node.peer_id().ok()?,
+        addresses: info.addrs,
+    })
+}
+
+async fn handle_beacon(
+    beacon: Beacon,
+    sender: SocketAddr,
+    own_peer_id: Option<&str>,
+    app_handle: &AppHandle,
+) {
+    if beacon.magic != BEACON_MAGIC || Some(beacon.peer_id.as_str()) == own_peer_id {
+        return;
+    }
+    if is_peer_banned(&beacon.peer_id, app_handle).await {
+        return;
+    }
+
+    let addresses = reachable_addresses(&beacon.addresses, sender.ip());
+    if addresses.is_empty() {
+        return;
+    }
+
+    let should_dial = {
+        let mut peers = LAN_PEERS.lock().await;
+        if !peers.contains_key(&beacon.peer_id) && peers.len() >= MAX_LAN_PEERS {
+            tracing::debug!("Ignoring LAN peer {}, too many peers", beacon.peer_id);
+            return;
+        }
+        let peer = peers.entry(beacon.peer_id.clone()).or_insert_with(|| {
+            info!("Found LAN peer {} at {}", beacon.peer_id, sender.ip());
+            DiscoveredPeer {
+                addresses: Vec::new(),
+                last_seen: Instant::now(),
+                last_seen_unix: 0,
+                last_dial: None,
+                connected: false,
+            }
+        });
+        peer.addresses = addresses.clone();
+        peer.last_seen = Instant::now();
+        peer.last_seen_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let due = peer
+            .last_dial
+            .map(|last_dial| last_dial.elapsed() >= REDIAL_INTERVAL)
+            .unwrap_or(true);
+        if due {
+            peer.last_dial = Some(Instant::now());
+        }
+        due
+    };
+
+    if should_dial {
+        // Dial in the background so a slow peer does not hold up the beacons
+        tokio::spawn(dial_peer(beacon.peer_id, addresses, app_handle.clone()));
+    }
+}
+
+async fn dial_peer(peer_id: String, addresses: Vec<String>, app_handle: AppHandle) {
+    // Same path as a manual connect from the UI
+    let connected = match get_storage_manager_with_handle(Some(app_handle)).await {
+        Ok(manager) => match manager.connect_to_peer(peer_id.clone(), addresses).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!("Failed to dial LAN peer {}: {}", peer_id, e);
+                false
+            }
+        },
+        Err(_) => false,
+    };
+
+    if let Some(peer) = LAN_PEERS.lock().await.get_mut(&peer_id) {
+        peer.connected = connected;
+    }
+}
+
+async fn run_lan_discovery(socket: UdpSocket, app_handle: AppHandle) {
+    let broadcast = SocketAddr::from((Ipv4Addr::BROADCAST, LAN_DISCOVERY_PORT));
+    let mut interval = tokio::time::interval(BEACON_INTERVAL);
+    let mut buffer = vec![0u8; 4096];
+    let mut own_peer_id: Option<String> = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                LAN_PEERS
+                    .lock()
+                    .await
+                    .retain(|_, peer| peer.last_seen.elapsed() < PEER_TIMEOUT);
+
+                if let Some(beacon) = local_beacon(&app_handle).await {
+                    own_peer_id = Some(beacon.peer_id.clone());
+                    if let Ok(payload) = serde_json::to_vec(&beacon) {
+                        if let Err(e) = socket.send_to(&payload, broadcast).await {
+                            warn!("Failed to send LAN beacon: {}", e);
+                        }
+                    }
+                }
+            }
+            received = socket.recv_from(&mut buffer) => {
+                let Ok((len, sender)) = received else { continue };
+                if let Ok(beacon) = serde_json::from_slice::<Beacon>(&buffer[..len]) {
+                    handle_beacon(beacon, sender, own_peer_id.as_deref(), &app_handle).await;
+                }
+            }
+        }
+    }
+}
+
+async fn start_lan_discovery(app_handle: AppHandle) -> Result<(), StorageError> {
+    let mut task = LAN_TASK.lock().await;
+    if task.is_some() {
+        return Ok(());
+    }
+
+    let socket = UdpSocket::bind(SocketAddr::from((
+        Ipv4Addr::UNSPECIFIED,
+        LAN_DISCOVERY_PORT,
+    )))
+    .await
+    .map_err(|e| {
+        StorageError::Io(format!(
+            "Failed to bind LAN discovery port {}: {}",
+            LAN_DISCOVERY_PORT, e
+        ))
+    })?;
+    socket
+        .set_broadcast(true)
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    info!("LAN discovery listening on port {}", LAN_DISCOVERY_PORT);
+    *task = Some(tokio::spawn(run_lan_discovery(socket, app_handle)));
+    Ok(())
+}
+
+async fn stop_lan_discovery() {
+    if let Some(task) = LAN_TASK.lock().await.take() {
+        task.abort();
+        info!("LAN discovery stopped");
+    }
+    LAN_PEERS.lock().await.clear();
+}
+
+/// Starts or stops local discovery to match the active profile
+pub async fn apply_lan_mode(app_handle: AppHandle) -> Result<(), StorageError> {
+    if load_settings(&app_handle).active_profile().lan_mode {
+        start_lan_discovery(app_handle).await
+    } else {
+        stop_lan_discovery().await;
+        Ok(())
+    }
+}
+
+/// Turns LAN mode on or off for the active profile and restarts the node with
+/// the matching bootstrap configuration
+pub async fn set_lan_mode_with_handle(
+    enabled: bool,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    let mut settings = load_settings(&app_handle);
+    let mut profile = settings.active_profile();
+    profile.lan_mode = enabled;
+    match settings
+        .profiles
+        .iter_mut()
+        .find(|existing| existing.name == profile.name)
+    {
+        Some(existing) => *existing = profile.clone(),
+        None => settings.profiles.push(profile.clone()),
+    }
+    save_settings(&app_handle, &settings)?;
+
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    manager
+        .switch_config(create_codex_config_for_profile(&app_handle, &profile)?)
+        .await?;
+    apply_lan_mode(app_handle).await?;
+
+    Ok(profile)
+}
+
+pub async fn list_lan_peers() -> Vec<LanPeer> {
+    LAN_PEERS
+        .lock()
+        .await
+        .iter()
+        .map(|(peer_id, peer)| LanPeer {
+            peer_id: peer_id.clone(),
+            addresses: peer.addresses.clone(),
+            last_seen: peer.last_seen_unix,
+            connected: peer.connected,
+        })
+        .collect()
+}
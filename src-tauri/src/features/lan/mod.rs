@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod lan;
+
+pub use commands::*;
+pub use lan::*;
@@ -0,0 +1,19 @@
+use crate::features::logging::{recent_logs, set_log_level_with_handle, LogEntry};
+use crate::features::settings::LogLevelSetting;
+use crate::features::shared::map_storage_error;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn get_recent_logs(
+    limit: Option<usize>,
+    min_level: Option<LogLevelSetting>,
+) -> Result<Vec<LogEntry>, String> {
+    Ok(recent_logs(limit, min_level))
+}
+
+#[tauri::command]
+pub async fn set_log_level(level: LogLevelSetting, app_handle: AppHandle) -> Result<(), String> {
+    set_log_level_with_handle(level, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
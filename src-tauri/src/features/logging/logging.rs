@@ -0,0 +1,205 @@
+use codex_bindings::update_log_level;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+use crate::features::connection::{node_log_level, STORAGE_MANAGER};
+use crate::features::settings::{load_settings, save_settings, LogLevelSetting};
+use crate::features::shared::StorageError;
+
+/// Event emitted to the frontend for every log line
+pub const LOG_EVENT: &str = "log-entry";
+
+const LOG_FILE_PREFIX: &str = "storeman";
+const MAX_LOG_FILES: usize = 7;
+const RECENT_LOG_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub level: LogLevelSetting,
+    pub target: String,
+    pub message: String,
+}
+
+static CURRENT_LEVEL: RwLock<LogLevelSetting> = RwLock::new(LogLevelSetting::Info);
+static RECENT_LOGS: Lazy<Mutex<VecDeque<LogEntry>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_LOG_CAPACITY)));
+static LOG_APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+static FILTER_HANDLE: OnceCell<reload::Handle<LevelFilter, Registry>> = OnceCell::new();
+// Dropping the guard would stop the background writer of the log file
+static LOG_GUARD: OnceCell<WorkerGuard> = OnceCell::new();
+
+fn level_filter(level: LogLevelSetting) -> LevelFilter {
+    match level {
+        LogLevelSetting::Trace => LevelFilter::TRACE,
+        LogLevelSetting::Debug => LevelFilter::DEBUG,
+        LogLevelSetting::Info => LevelFilter::INFO,
+        LogLevelSetting::Warn => LevelFilter::WARN,
+        LogLevelSetting::Error => LevelFilter::ERROR,
+    }
+}
+
+fn entry_level(level: &tracing::Level) -> LogLevelSetting {
+    match *level {
+        tracing::Level::TRACE => LogLevelSetting::Trace,
+        tracing::Level::DEBUG => LogLevelSetting::Debug,
+        tracing::Level::INFO => LogLevelSetting::Info,
+        tracing::Level::WARN => LogLevelSetting::Warn,
+        tracing::Level::ERROR => LogLevelSetting::Error,
+    }
+}
+
+/// Returns the level currently applied to the app and the node
+pub fn current_log_level() -> LogLevelSetting {
+    *CURRENT_LEVEL.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Keeps the latest log lines in memory and forwards them to the frontend
+struct CaptureLayer;
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let entry = LogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or_default(),
+            level: entry_level(event.metadata().level()),
+            target: event.metadata().target().to_string(),
+            message: visitor.message + &visitor.fields,
+        };
+
+        {
+            let mut logs = RECENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+            if logs.len() == RECENT_LOG_CAPACITY {
+                logs.pop_front();
+            }
+            logs.push_back(entry.clone());
+        }
+
+        if let Some(app_handle) = LOG_APP_HANDLE.get() {
+            let _ = app_handle.emit(LOG_EVENT, entry);
+        }
+    }
+}
+
+/// Installs the global subscriber: daily rotated files in the app log dir,
+/// stdout, and the in-memory buffer behind `get_recent_logs`
+pub fn init_logging(app_handle: &AppHandle) -> Result<(), StorageError> {
+    let log_dir = app_handle
+        .path()
+        .app_log_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    std::fs::create_dir_all(&log_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+    let level = load_settings(app_handle).logging.level;
+    *CURRENT_LEVEL.write().unwrap_or_else(|e| e.into_inner()) = level;
+    let (filter, filter_handle) = reload::Layer::new(level_filter(level));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(file_writer).with_ansi(false))
+        .with(fmt::layer())
+        .with(CaptureLayer)
+        .try_init()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let _ = LOG_GUARD.set(guard);
+    let _ = FILTER_HANDLE.set(filter_handle);
+    let _ = LOG_APP_HANDLE.set(app_handle.clone());
+
+    tracing::info!("Logging to {}", log_dir.display());
+    Ok(())
+}
+
+/// Applies `level` to the app log and to the running node without restarting it
+pub async fn apply_log_level(level: LogLevelSetting) -> Result<(), StorageError> {
+    *CURRENT_LEVEL.write().unwrap_or_else(|e| e.into_inner()) = level;
+
+    if let Some(handle) = FILTER_HANDLE.get() {
+        handle
+            .reload(level_filter(level))
+            .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    }
+
+    // Nodes created later pick the level up from their config
+    if let Some(manager) = STORAGE_MANAGER.get() {
+        if let Ok(node) = manager.get_node().await {
+            if node.is_started() {
+                update_log_level(&node, node_log_level(level))
+                    .await
+                    .map_err(|e| StorageError::Configuration(e.to_string()))?;
+            }
+        }
+    }
+
+    tracing::info!("Log level set to {:?}", level);
+    Ok(())
+}
+
+pub async fn set_log_level_with_handle(
+    level: LogLevelSetting,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let mut settings = load_settings(&app_handle);
+    settings.logging.level = level;
+    save_settings(&app_handle, &settings)?;
+    apply_log_level(level).await
+}
+
+/// Returns the newest buffered log lines, oldest first
+pub fn recent_logs(limit: Option<usize>, min_level: Option<LogLevelSetting>) -> Vec<LogEntry> {
+    let logs = RECENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+    let matching: Vec<LogEntry> = logs
+        .iter()
+        .filter(|entry| min_level.map(|min| entry.level >= min).unwrap_or(true))
+        .cloned()
+        .collect();
+
+    let skip = limit
+        .map(|limit| matching.len().saturating_sub(limit))
+        .unwrap_or(0);
+    matching.into_iter().skip(skip).collect()
+}
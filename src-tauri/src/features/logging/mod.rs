@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod logging;
+
+pub use commands::*;
+pub use logging::*;
@@ -0,0 +1,47 @@
+use crate::features::maintenance::{
+    migrate_repository_with_handle, remove_datasets_with_handle, reset_repository_with_handle,
+    verify_repository_with_handle,
+};
+use crate::features::settings::RepoBackend;
+use crate::features::shared::{
+    map_storage_error, DatasetRemovalResult, MigrationResult, RepositoryReport,
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn verify_repository(app_handle: AppHandle) -> Result<RepositoryReport, String> {
+    verify_repository_with_handle(app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn remove_datasets(
+    confirmed: Vec<String>,
+    app_handle: AppHandle,
+) -> Result<DatasetRemovalResult, String> {
+    remove_datasets_with_handle(confirmed, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn reset_repository(
+    confirm: String,
+    keep_identity: bool,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    reset_repository_with_handle(confirm, keep_identity, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn migrate_repository(
+    backend: RepoBackend,
+    app_handle: AppHandle,
+) -> Result<MigrationResult, String> {
+    migrate_repository_with_handle(backend, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
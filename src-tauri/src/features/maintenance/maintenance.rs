@@ -0,0 +1,248 @@
+use codex_bindings::{
+    delete, download_cancel, download_chunk, download_init, manifests, space, CodexNode,
+    DownloadOptions,
+};
+use std::path::Path;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::features::connection::{
+    get_storage_manager_with_handle, profile_data_dir, StorageManager,
+};
+use crate::features::identity::NODE_KEY_FILE;
+use crate::features::settings::load_settings;
+use crate::features::shared::{
+    DatasetIssue, DatasetRemovalResult, OperationStage, ProgressMessage, RepositoryReport,
+    StorageError,
+};
+use crate::features::watch::clear_watch_uploads;
+
+// Only one maintenance job may touch the repository at a time
+pub(crate) static MAINTENANCE_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn dir_size(dir: &Path) -> u64 {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
+/// Reads every block of a dataset from the local store only, so a missing or
+/// corrupted block fails instead of being fetched from the network. Each chunk is
+/// handed to `on_chunk` in order.
+pub(crate) async fn read_local_dataset(
+    node: &CodexNode,
+    cid: &str,
+    dataset_size: usize,
+    block_size: usize,
+    mut on_chunk: impl FnMut(&[u8]) -> Result<(), StorageError>,
+) -> Result<(), StorageError> {
+    let download_options = DownloadOptions::new(cid).chunk_size(block_size).local(true);
+    download_init(node, cid, &download_options)
+        .await
+        .map_err(|e| StorageError::Download(e.to_string()))?;
+
+    let result = async {
+        let mut read = 0;
+        while read < dataset_size {
+            let chunk = download_chunk(node, cid)
+                .await
+                .map_err(|e| StorageError::Download(e.to_string()))?;
+            if chunk.is_empty() {
+                break;
+            }
+            on_chunk(&chunk)?;
+            read += chunk.len();
+        }
+
+        if read != dataset_size {
+            return Err(StorageError::Download(format!(
+                "Read {} of {} bytes",
+                read, dataset_size
+            )));
+        }
+        Ok(())
+    }
+    .await;
+
+    let _ = download_cancel(node, cid).await;
+    result
+}
+
+/// Checks every locally stored dataset against its manifest and counts blocks
+/// that no manifest references
+pub async fn verify_repository_with_handle(
+    app_handle: AppHandle,
+) -> Result<RepositoryReport, StorageError> {
+    let _guard = MAINTENANCE_LOCK.lock().await;
+    let manager = get_storage_manager_with_handle(Some(app_handle)).await?;
+    let node = manager.get_node().await?;
+    run_verification(&manager, &node).await
+}
+
+async fn verify_node(
+    manager: &StorageManager,
+    node: &CodexNode,
+    operation_id: &str,
+) -> Result<RepositoryReport, StorageError> {
+    let start_time = std::time::Instant::now();
+
+    let datasets = manifests(node)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let usage = space(node)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let total = datasets.len();
+    let mut unreadable = Vec::new();
+    let mut referenced_blocks = 0;
+
+    for (index, dataset) in datasets.iter().enumerate() {
+        let manifest = &dataset.manifest;
+        let block_size = manifest.block_size.max(1);
+        // Data blocks plus the block holding the manifest itself
+        referenced_blocks += manifest.dataset_size.div_ceil(block_size) + 1;
+
+        if let Err(e) = read_local_dataset(
+            node,
+            &dataset.cid,
+            manifest.dataset_size,
+            block_size,
+            |_| Ok(()),
+        )
+        .await
+        {
+            unreadable.push(DatasetIssue {
+                cid: dataset.cid.clone(),
+                error: e.to_string(),
+            });
+        }
+
+        let progress_msg = ProgressMessage::new(operation_id.to_string())
+            .with_stage(OperationStage::Verifying)
+            .with_bytes(index + 1, Some(total))
+            .with_message(format!("Verified {} of {} datasets", index + 1, total));
+        manager.send_progress(operation_id, progress_msg).await;
+    }
+
+    Ok(RepositoryReport {
+        datasets: total,
+        verified: total - unreadable.len(),
+        unreadable,
+        total_blocks: usage.total_blocks,
+        referenced_blocks,
+        orphaned_blocks: usage.total_blocks.saturating_sub(referenced_blocks),
+        used_bytes: usage.quota_used_bytes,
+        quota_bytes: usage.quota_max_bytes,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+    })
+}
+
+async fn run_verification(
+    manager: &StorageManager,
+    node: &CodexNode,
+) -> Result<RepositoryReport, StorageError> {
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+
+    let operation_id = Uuid::new_v4().to_string();
+    let _progress = manager.track_progress(operation_id.clone()).await?;
+
+    let result = verify_node(manager, node, &operation_id).await;
+
+    let final_stage = match &result {
+        Ok(_) => OperationStage::Completed,
+        Err(e) => OperationStage::Failed(e.to_string()),
+    };
+    manager
+        .send_progress(
+            &operation_id,
+            ProgressMessage::new(operation_id.clone()).with_stage(final_stage),
+        )
+        .await;
+
+    result
+}
+
+/// Deletes the datasets the user picked from the unreadable ones of a verification
+/// report.
+///
+/// The repository is verified again first and only CIDs that still cannot be read
+/// locally are deleted, so a stale or edited list cannot remove intact content.
+/// Orphaned blocks are left alone, the bindings cannot reclaim them.
+pub async fn remove_datasets_with_handle(
+    confirmed: Vec<String>,
+    app_handle: AppHandle,
+) -> Result<DatasetRemovalResult, StorageError> {
+    let _guard = MAINTENANCE_LOCK.lock().await;
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let node = manager.get_node().await?;
+    let report = run_verification(&manager, &node).await?;
+
+    let data_dir = profile_data_dir(&app_handle, &load_settings(&app_handle).active_profile);
+    let size_before = dir_size(&data_dir);
+
+    let mut removed = Vec::new();
+    for cid in confirmed {
+        if !report.unreadable.iter().any(|dataset| dataset.cid == cid) {
+            warn!("Keeping {}, it no longer fails verification", cid);
+            continue;
+        }
+        match delete(&node, &cid).await {
+            Ok(_) => removed.push(cid),
+            Err(e) => error!("Failed to delete unreadable dataset {}: {}", cid, e),
+        }
+    }
+
+    Ok(DatasetRemovalResult {
+        removed,
+        size_before,
+        size_after: dir_size(&data_dir),
+    })
+}
+
+/// Stops the node and wipes the repository of the active profile.
+///
+/// `confirm` must repeat the active profile name. With `keep_identity` the node
+/// key survives, so the node keeps its peer ID.
+pub async fn reset_repository_with_handle(
+    confirm: String,
+    keep_identity: bool,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let profile = load_settings(&app_handle).active_profile;
+    if confirm != profile {
+        return Err(StorageError::Configuration(format!(
+            "Type the profile name '{}' to confirm the reset",
+            profile
+        )));
+    }
+
+    let _guard = MAINTENANCE_LOCK.lock().await;
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    manager.stop_node().await?;
+
+    let keep_files: &[&str] = if keep_identity { &[NODE_KEY_FILE] } else { &[] };
+    manager
+        .reset_repository(&profile_data_dir(&app_handle, &profile), keep_files)
+        .await?;
+
+    // Nothing recorded as uploaded is stored anymore
+    clear_watch_uploads(&app_handle).await
+}
@@ -0,0 +1,340 @@
+use codex_bindings::manifests;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::features::connection::{
+    create_codex_config_at, get_storage_manager_with_handle, profile_data_dir, StorageManager,
+};
+use crate::features::identity::NODE_KEY_FILE;
+use crate::features::maintenance::maintenance::{read_local_dataset, MAINTENANCE_LOCK};
+use crate::features::settings::{load_settings, save_settings, NodeProfile, RepoBackend};
+use crate::features::shared::{MigrationResult, OperationStage, ProgressMessage, StorageError};
+use crate::features::upload::upload_file_with_progress;
+
+/// Event carrying a `ProgressMessage` for every migrated dataset
+pub const MIGRATION_PROGRESS_EVENT: &str = "repo-migration-progress";
+
+struct StagedDataset {
+    cid: String,
+    path: PathBuf,
+}
+
+fn emit_progress(app_handle: &AppHandle, progress: ProgressMessage) {
+    if let Err(e) = app_handle.emit(MIGRATION_PROGRESS_EVENT, progress) {
+        warn!("Failed to emit migration progress: {}", e);
+    }
+}
+
+/// Copies every dataset out of the running node into `staging_dir`, keeping the
+/// original filenames so re-uploading produces the same manifests.
+///
+/// Only the local store is read, a dataset missing blocks fails the export instead
+/// of fetching them from the network.
+async fn export_datasets(
+    manager: &StorageManager,
+    staging_dir: &Path,
+    operation_id: &str,
+    app_handle: &AppHandle,
+) -> Result<Vec<StagedDataset>, StorageError> {
+    let node = manager.get_node().await?;
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+
+    let datasets = manifests(&node)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let total = datasets.len();
+
+    let mut staged = Vec::with_capacity(total);
+    for (index, dataset) in datasets.into_iter().enumerate() {
+        let filename = Some(dataset.manifest.filename)
+            .filter(|name| !name.is_empty() && !name.contains(['/', '\\']))
+            .unwrap_or_else(|| dataset.cid.clone());
+        let dataset_dir = staging_dir.join(&dataset.cid);
+        std::fs::create_dir_all(&dataset_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+        let path = dataset_dir.join(filename);
+
+        let mut file = File::create(&path).map_err(|e| StorageError::Io(e.to_string()))?;
+        read_local_dataset(
+            &node,
+            &dataset.cid,
+            dataset.manifest.dataset_size,
+            dataset.manifest.block_size.max(1),
+            |chunk| {
+                file.write_all(chunk)
+                    .map_err(|e| StorageError::Io(e.to_string()))
+            },
+        )
+        .await?;
+
+        emit_progress(
+            app_handle,
+            ProgressMessage::new(operation_id.to_string())
+                .with_stage(OperationStage::Downloading)
+                .with_bytes(index + 1, Some(total))
+                .with_message(format!("Exported {} of {} datasets", index + 1, total)),
+        );
+        staged.push(StagedDataset {
+            cid: dataset.cid,
+            path,
+        });
+    }
+
+    Ok(staged)
+}
+
+/// Uploads the staged datasets into the current node and returns the CIDs that
+/// did not come back identical
+async fn import_datasets(
+    staged: &[StagedDataset],
+    operation_id: &str,
+    app_handle: &AppHandle,
+) -> Vec<String> {
+    let total = staged.len();
+    let mut mismatched = Vec::new();
+
+    for (index, dataset) in staged.iter().enumerate() {
+        match upload_file_with_progress(dataset.path.clone(), app_handle.clone()).await {
+            Ok(result) if result.cid == dataset.cid => {}
+            Ok(result) => {
+                error!(
+                    "Migrated dataset {} came back as {}",
+                    dataset.cid, result.cid
+                );
+                mismatched.push(dataset.cid.clone());
+            }
+            Err(e) => {
+                error!("Failed to migrate dataset {}: {}", dataset.cid, e);
+                mismatched.push(dataset.cid.clone());
+            }
+        }
+
+        emit_progress(
+            app_handle,
+            ProgressMessage::new(operation_id.to_string())
+                .with_stage(OperationStage::Uploading)
+                .with_bytes(index + 1, Some(total))
+                .with_message(format!("Imported {} of {} datasets", index + 1, total)),
+        );
+    }
+
+    mismatched
+}
+
+/// Moves the migrated repository into `data_dir`, keeping the current one at
+/// `old_dir`. If the second rename fails the current repository is moved back.
+fn swap_repository(
+    data_dir: &Path,
+    migrating_dir: &Path,
+    old_dir: &Path,
+) -> Result<(), StorageError> {
+    std::fs::rename(data_dir, old_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    if let Err(e) = std::fs::rename(migrating_dir, data_dir) {
+        if let Err(restore) = std::fs::rename(old_dir, data_dir) {
+            error!(
+                "Failed to move the repository back from {}: {}",
+                old_dir.display(),
+                restore
+            );
+        }
+        return Err(StorageError::Io(e.to_string()));
+    }
+    Ok(())
+}
+
+/// Points the node back at the repository in `data_dir`. If that fails too the node
+/// is released, so nothing keeps a half migrated repository open.
+async fn reopen_repository(
+    manager: &StorageManager,
+    data_dir: &Path,
+    profile: &NodeProfile,
+    start: bool,
+) -> Result<(), StorageError> {
+    let result = async {
+        manager
+            .switch_config(create_codex_config_at(data_dir, profile)?)
+            .await?;
+        if start {
+            manager.start_node().await?;
+        }
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = &result {
+        error!(
+            "Failed to reopen the repository at {}: {}",
+            data_dir.display(),
+            e
+        );
+        if let Err(e) = manager.release_node().await {
+            error!("Failed to release node: {}", e);
+        }
+    }
+    result
+}
+
+/// Adds the reopen failure to `e`, the user has to restart the node by hand then
+fn with_reopen_error(e: StorageError, reopened: Result<(), StorageError>) -> StorageError {
+    match reopened {
+        Ok(()) => e,
+        Err(reopen_error) => StorageError::Configuration(format!(
+            "{}, and the old repository could not be reopened: {}",
+            e, reopen_error
+        )),
+    }
+}
+
+/// Moves the active profile's repository to another backend.
+///
+/// Every dataset is exported, re-uploaded into a fresh repository next to the
+/// current one and checked to have kept its CID. The old repository is only
+/// removed once all datasets verified; otherwise the node goes back to it.
+pub async fn migrate_repository_with_handle(
+    backend: RepoBackend,
+    app_handle: AppHandle,
+) -> Result<MigrationResult, StorageError> {
+    let _guard = MAINTENANCE_LOCK.lock().await;
+    let start_time = std::time::Instant::now();
+
+    let profile = load_settings(&app_handle).active_profile();
+    if profile.repo_backend == backend {
+        return Err(StorageError::Configuration(format!(
+            "Profile '{}' already uses the {:?} backend",
+            profile.name, backend
+        )));
+    }
+
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let data_dir = profile_data_dir(&app_handle, &profile.name);
+    let migrating_dir = data_dir.with_file_name("node_data.migrating");
+    let staging_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join("migration");
+
+    let operation_id = Uuid::new_v4().to_string();
+    emit_progress(
+        &app_handle,
+        ProgressMessage::new(operation_id.clone()).with_stage(OperationStage::Initializing),
+    );
+
+    let result = async {
+        let staged = export_datasets(&manager, &staging_dir, &operation_id, &app_handle).await?;
+
+        // The new repository keeps the node key so the peer ID does not change
+        if migrating_dir.exists() {
+            std::fs::remove_dir_all(&migrating_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+        std::fs::create_dir_all(&migrating_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+        if data_dir.join(NODE_KEY_FILE).is_file() {
+            std::fs::copy(
+                data_dir.join(NODE_KEY_FILE),
+                migrating_dir.join(NODE_KEY_FILE),
+            )
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        }
+
+        let migrated_profile = NodeProfile {
+            repo_backend: backend,
+            ..profile.clone()
+        };
+        manager
+            .switch_config(create_codex_config_at(&migrating_dir, &migrated_profile)?)
+            .await?;
+
+        let mismatched = import_datasets(&staged, &operation_id, &app_handle).await;
+        Ok::<_, StorageError>((staged.len(), mismatched, migrated_profile))
+    }
+    .await;
+
+    let _ = std::fs::remove_dir_all(&staging_dir);
+
+    let (datasets, migrated_profile) = match result {
+        Ok((datasets, mismatched, migrated_profile)) if mismatched.is_empty() => {
+            (datasets, migrated_profile)
+        }
+        outcome => {
+            // Go back to the untouched repository
+            let reopened = reopen_repository(&manager, &data_dir, &profile, false).await;
+            let _ = std::fs::remove_dir_all(&migrating_dir);
+
+            let e = match outcome {
+                Ok((_, mismatched, _)) => StorageError::Configuration(format!(
+                    "{} datasets failed verification, the old repository was kept",
+                    mismatched.len()
+                )),
+                Err(e) => e,
+            };
+            let e = with_reopen_error(e, reopened);
+            emit_progress(
+                &app_handle,
+                ProgressMessage::new(operation_id.clone())
+                    .with_stage(OperationStage::Failed(e.to_string())),
+            );
+            return Err(e);
+        }
+    };
+
+    // Swap the verified repository into place
+    let was_running = manager.get_node().await?.is_started();
+    manager.release_node().await?;
+    let old_dir = data_dir.with_file_name("node_data.old");
+    if let Err(e) = swap_repository(&data_dir, &migrating_dir, &old_dir) {
+        // Reopen the repository the node had before
+        let reopened = reopen_repository(&manager, &data_dir, &profile, was_running).await;
+        let e = with_reopen_error(e, reopened);
+        emit_progress(
+            &app_handle,
+            ProgressMessage::new(operation_id.clone())
+                .with_stage(OperationStage::Failed(e.to_string())),
+        );
+        return Err(e);
+    }
+
+    // Settings may have changed while the datasets were copied
+    let mut settings = load_settings(&app_handle);
+    match settings
+        .profiles
+        .iter_mut()
+        .find(|existing| existing.name == profile.name)
+    {
+        Some(existing) => existing.repo_backend = backend,
+        None => settings.profiles.push(migrated_profile.clone()),
+    }
+    save_settings(&app_handle, &settings)?;
+
+    manager
+        .switch_config(create_codex_config_at(&data_dir, &migrated_profile)?)
+        .await?;
+    if was_running {
+        manager.start_node().await?;
+    }
+
+    if let Err(e) = std::fs::remove_dir_all(&old_dir) {
+        warn!(
+            "Failed to remove old repository {}: {}",
+            old_dir.display(),
+            e
+        );
+    }
+
+    emit_progress(
+        &app_handle,
+        ProgressMessage::new(operation_id)
+            .with_stage(OperationStage::Completed)
+            .with_message(format!("Migrated {} datasets", datasets)),
+    );
+
+    Ok(MigrationResult {
+        backend,
+        datasets,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+    })
+}
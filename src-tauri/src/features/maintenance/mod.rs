@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod maintenance;
+pub mod migration;
+
+pub use commands::*;
+pub use maintenance::*;
+pub use migration::*;
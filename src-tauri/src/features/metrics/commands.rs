@@ -0,0 +1,33 @@
+use crate::features::metrics::{
+    get_metrics_endpoint_status, network_stats, start_metrics_endpoint, stop_metrics_endpoint,
+};
+use crate::features::settings::load_settings;
+use crate::features::shared::{map_storage_error, MetricsEndpointStatus, NetworkStats};
+use tauri::AppHandle;
+
+/// `since` is a Unix timestamp in seconds, only newer samples are returned
+#[tauri::command]
+pub async fn get_network_stats(since: Option<u64>) -> Result<NetworkStats, String> {
+    Ok(network_stats(since).await)
+}
+
+#[tauri::command]
+pub async fn start_metrics(
+    port: Option<u16>,
+    app_handle: AppHandle,
+) -> Result<MetricsEndpointStatus, String> {
+    let port = port.unwrap_or_else(|| load_settings(&app_handle).metrics.port);
+    start_metrics_endpoint(port, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn stop_metrics() -> Result<(), String> {
+    stop_metrics_endpoint().await.map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn metrics_status() -> Result<MetricsEndpointStatus, String> {
+    Ok(get_metrics_endpoint_status().await)
+}
@@ -0,0 +1,138 @@
+use codex_bindings::{connected_peers, debug};
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::shared::{NetworkSample, NetworkStats};
+
+/// Event emitted with every new `NetworkSample`
+pub const NETWORK_STATS_EVENT: &str = "network-stats";
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+// One hour of samples
+const SAMPLE_LIMIT: usize = 720;
+
+static SAMPLES: Lazy<Mutex<VecDeque<NetworkSample>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(SAMPLE_LIMIT)));
+static SAMPLER_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+fn rate(current: u64, previous: u64, elapsed_secs: u64) -> f64 {
+    if elapsed_secs == 0 {
+        return 0.0;
+    }
+    current.saturating_sub(previous) as f64 / elapsed_secs as f64
+}
+
+async fn take_sample(
+    app_handle: &AppHandle,
+    previous: Option<&NetworkSample>,
+) -> Option<NetworkSample> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone()))
+        .await
+        .ok()?;
+    let status = manager.get_status().await;
+    let counters = manager.transfer_counters().await;
+
+    let (connected_peers, dht_table_size) = match manager.get_node().await {
+        Ok(node) if node.is_started() => {
+            let peers = match connected_peers(&node).await {
+                Ok(peers) => peers.len(),
+                Err(e) => {
+                    warn!("Failed to sample connected peers: {}", e);
+                    0
+                }
+            };
+            let dht_table_size = match debug(&node).await {
+                Ok(info) => info.table.nodes.len(),
+                Err(e) => {
+                    warn!("Failed to sample node debug info: {}", e);
+                    0
+                }
+            };
+            (peers, dht_table_size)
+        }
+        _ => (0, 0),
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (bytes_in_per_sec, bytes_out_per_sec) = match previous {
+        Some(previous) => {
+            let elapsed = timestamp.saturating_sub(previous.timestamp);
+            (
+                rate(counters.bytes_in, previous.bytes_in, elapsed),
+                rate(counters.bytes_out, previous.bytes_out, elapsed),
+            )
+        }
+        None => (0.0, 0.0),
+    };
+
+    Some(NetworkSample {
+        timestamp,
+        status,
+        connected_peers,
+        dht_table_size,
+        // Not available from the bindings, see `NetworkSample`
+        blocks_sent: None,
+        blocks_received: None,
+        bytes_in: counters.bytes_in,
+        bytes_out: counters.bytes_out,
+        bytes_in_per_sec,
+        bytes_out_per_sec,
+    })
+}
+
+/// Starts sampling the node in the background, once per app run
+pub async fn start_network_sampler(app_handle: AppHandle) {
+    let mut task = SAMPLER_TASK.lock().await;
+    if task.is_some() {
+        return;
+    }
+
+    *task = Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let previous = SAMPLES.lock().await.back().cloned();
+            let Some(sample) = take_sample(&app_handle, previous.as_ref()).await else {
+                continue;
+            };
+
+            {
+                let mut samples = SAMPLES.lock().await;
+                if samples.len() == SAMPLE_LIMIT {
+                    samples.pop_front();
+                }
+                samples.push_back(sample.clone());
+            }
+
+            let _ = app_handle.emit(NETWORK_STATS_EVENT, sample);
+        }
+    }));
+}
+
+/// Returns the buffered samples, optionally only those newer than `since`
+pub async fn network_stats(since: Option<u64>) -> NetworkStats {
+    let samples = SAMPLES.lock().await;
+    NetworkStats {
+        interval_secs: SAMPLE_INTERVAL.as_secs(),
+        samples: samples
+            .iter()
+            .filter(|sample| since.map(|since| sample.timestamp > since).unwrap_or(true))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Most recent sample, if the sampler has run at least once
+pub async fn latest_network_sample() -> Option<NetworkSample> {
+    SAMPLES.lock().await.back().cloned()
+}
@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod metrics;
+pub mod prometheus;
+
+pub use commands::*;
+pub use metrics::*;
+pub use prometheus::*;
@@ -0,0 +1,293 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use codex_bindings::space;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use tauri::AppHandle;
+use tokio::sync::{oneshot, Mutex};
+use tracing::{error, info, warn};
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::metrics::latest_network_sample;
+use crate::features::settings::MetricsSettings;
+use crate::features::shared::{
+    error_counts, MetricsEndpointStatus, StorageConnectionStatus, StorageError,
+};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+struct RunningExporter {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+// At most one metrics endpoint runs at a time
+static EXPORTER: Mutex<Option<RunningExporter>> = Mutex::const_new(None);
+
+/// Appends one metric family with its HELP and TYPE lines
+fn write_metric<L: AsRef<str>>(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: &[(L, String)],
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        let labels = labels.as_ref();
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+async fn render_metrics(app_handle: &AppHandle) -> Result<String, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let counters = manager.transfer_counters().await;
+    let status = manager.get_status().await;
+    let mut out = String::new();
+
+    write_metric(
+        &mut out,
+        "storeman_transfers_completed_total",
+        "counter",
+        "Finished uploads and downloads",
+        &[
+            (
+                "direction=\"upload\"",
+                counters.uploads_completed.to_string(),
+            ),
+            (
+                "direction=\"download\"",
+                counters.downloads_completed.to_string(),
+            ),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "storeman_transfers_failed_total",
+        "counter",
+        "Uploads and downloads that failed after they started",
+        &[("", counters.failed.to_string())],
+    );
+    write_metric(
+        &mut out,
+        "storeman_transfer_bytes_total",
+        "counter",
+        "Bytes transferred by uploads and downloads started in the app, seeding is not counted",
+        &[
+            ("direction=\"upload\"", counters.bytes_out.to_string()),
+            ("direction=\"download\"", counters.bytes_in.to_string()),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "storeman_transfer_duration_seconds_total",
+        "counter",
+        "Time spent in finished uploads and downloads",
+        &[
+            (
+                "direction=\"upload\"",
+                (counters.upload_duration_ms as f64 / 1000.0).to_string(),
+            ),
+            (
+                "direction=\"download\"",
+                (counters.download_duration_ms as f64 / 1000.0).to_string(),
+            ),
+        ],
+    );
+
+    let errors: Vec<(String, String)> = error_counts()
+        .into_iter()
+        .map(|(kind, count)| (format!("kind=\"{}\"", kind), count.to_string()))
+        .collect();
+    write_metric(
+        &mut out,
+        "storeman_errors_total",
+        "counter",
+        "Errors returned to the app, by StorageError kind",
+        &errors,
+    );
+
+    let statuses = [
+        (StorageConnectionStatus::Disconnected, "disconnected"),
+        (StorageConnectionStatus::Connecting, "connecting"),
+        (StorageConnectionStatus::Initialized, "initialized"),
+        (StorageConnectionStatus::Connected, "connected"),
+        (StorageConnectionStatus::Error, "error"),
+    ];
+    let status_labels: Vec<(String, String)> = statuses
+        .iter()
+        .map(|(candidate, label)| {
+            (
+                format!("status=\"{}\"", label),
+                u8::from(*candidate == status).to_string(),
+            )
+        })
+        .collect();
+    write_metric(
+        &mut out,
+        "storeman_node_status",
+        "gauge",
+        "Current node status, 1 for the active one",
+        &status_labels,
+    );
+
+    let sample = latest_network_sample().await;
+    write_metric(
+        &mut out,
+        "storeman_connected_peers",
+        "gauge",
+        "Peers the node currently has a connection to",
+        &[(
+            "",
+            sample
+                .as_ref()
+                .map(|sample| sample.connected_peers)
+                .unwrap_or(0)
+                .to_string(),
+        )],
+    );
+    write_metric(
+        &mut out,
+        "storeman_dht_table_size",
+        "gauge",
+        "Nodes in the DHT routing table",
+        &[(
+            "",
+            sample
+                .as_ref()
+                .map(|sample| sample.dht_table_size)
+                .unwrap_or(0)
+                .to_string(),
+        )],
+    );
+
+    if let Ok(node) = manager.get_node().await {
+        if node.is_started() {
+            match space(&node).await {
+                Ok(usage) => {
+                    write_metric(
+                        &mut out,
+                        "storeman_quota_bytes",
+                        "gauge",
+                        "Storage quota of the node",
+                        &[("", usage.quota_max_bytes.to_string())],
+                    );
+                    write_metric(
+                        &mut out,
+                        "storeman_quota_used_bytes",
+                        "gauge",
+                        "Storage quota in use",
+                        &[("", usage.quota_used_bytes.to_string())],
+                    );
+                }
+                Err(e) => warn!("Failed to read node quota usage: {}", e),
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+async fn get_metrics(State(app_handle): State<AppHandle>) -> impl IntoResponse {
+    match render_metrics(&app_handle).await {
+        Ok(body) => ([(header::CONTENT_TYPE, CONTENT_TYPE)], body).into_response(),
+        Err(e) => (axum::http::StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response(),
+    }
+}
+
+pub async fn start_metrics_endpoint(
+    port: u16,
+    app_handle: AppHandle,
+) -> Result<MetricsEndpointStatus, StorageError> {
+    let mut exporter = EXPORTER.lock().await;
+    if let Some(running) = exporter.as_ref() {
+        if running.port == port {
+            return Ok(MetricsEndpointStatus::running(port));
+        }
+        return Err(StorageError::Configuration(format!(
+            "Metrics endpoint is already running on port {}",
+            running.port
+        )));
+    }
+
+    let router = Router::new()
+        .route("/metrics", get(get_metrics))
+        .with_state(app_handle);
+
+    // Metrics are only served to the local machine
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+        StorageError::Io(format!(
+            "Failed to bind metrics endpoint to {}: {}",
+            addr, e
+        ))
+    })?;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    tokio::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        if let Err(e) = server.await {
+            error!("Metrics endpoint error: {}", e);
+        }
+    });
+
+    info!("Metrics endpoint listening on http://{}/metrics", addr);
+
+    *exporter = Some(RunningExporter {
+        port,
+        shutdown: shutdown_tx,
+    });
+
+    Ok(MetricsEndpointStatus::running(port))
+}
+
+pub async fn stop_metrics_endpoint() -> Result<(), StorageError> {
+    let running = {
+        let mut exporter = EXPORTER.lock().await;
+        exporter.take()
+    };
+
+    if let Some(running) = running {
+        let _ = running.shutdown.send(());
+        info!("Metrics endpoint on port {} stopped", running.port);
+    }
+
+    Ok(())
+}
+
+pub async fn get_metrics_endpoint_status() -> MetricsEndpointStatus {
+    let exporter = EXPORTER.lock().await;
+    match exporter.as_ref() {
+        Some(running) => MetricsEndpointStatus::running(running.port),
+        None => MetricsEndpointStatus::stopped(),
+    }
+}
+
+/// Starts, stops or restarts the metrics endpoint so it matches the given settings
+pub async fn apply_metrics_settings(
+    settings: &MetricsSettings,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let status = get_metrics_endpoint_status().await;
+
+    if !settings.enabled {
+        return stop_metrics_endpoint().await;
+    }
+
+    if status.running && status.port != Some(settings.port) {
+        stop_metrics_endpoint().await?;
+    }
+
+    start_metrics_endpoint(settings.port, app_handle).await?;
+    Ok(())
+}
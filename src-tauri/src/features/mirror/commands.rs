@@ -0,0 +1,55 @@
+use crate::features::mirror::{
+    load_subscriptions, mirror_directory_with_handle, publish_directory_with_handle,
+    subscribe_directory_with_handle, unsubscribe_directory_with_handle, MirrorSubscription,
+};
+use crate::features::shared::{map_storage_error, MirrorSummary, PublishedDirectory};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn publish_directory(
+    path: String,
+    app_handle: AppHandle,
+) -> Result<PublishedDirectory, String> {
+    publish_directory_with_handle(path.into(), app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn mirror_directory(
+    root: String,
+    target: String,
+    remove_stale: bool,
+    app_handle: AppHandle,
+) -> Result<MirrorSummary, String> {
+    mirror_directory_with_handle(root, target.into(), remove_stale, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn subscribe_directory(
+    root: String,
+    target: String,
+    interval_secs: u64,
+    remove_stale: bool,
+    app_handle: AppHandle,
+) -> Result<MirrorSubscription, String> {
+    subscribe_directory_with_handle(root, target.into(), interval_secs, remove_stale, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn unsubscribe_directory(id: String, app_handle: AppHandle) -> Result<(), String> {
+    unsubscribe_directory_with_handle(id, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn list_mirror_subscriptions(
+    app_handle: AppHandle,
+) -> Result<Vec<MirrorSubscription>, String> {
+    Ok(load_subscriptions(&app_handle).await)
+}
@@ -0,0 +1,460 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::features::connection::transfers_paused;
+use crate::features::download::download_file_with_progress;
+use crate::features::naming::{is_name_address, resolve_name_with_handle};
+use crate::features::shared::{
+    load_json, save_json, MirrorSummary, PublishedDirectory, StorageError,
+};
+use crate::features::upload::upload_file_with_progress;
+use crate::features::watch::collect_files;
+
+const SUBSCRIPTIONS_FILE: &str = "mirror_subscriptions.json";
+const DIRECTORY_MANIFEST_VERSION: u32 = 1;
+
+/// Entry of a published directory, `path` is relative and always uses `/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryEntry {
+    pub path: String,
+    pub cid: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Listing uploaded as content itself, its CID is the root of the directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryManifest {
+    pub version: u32,
+    pub entries: Vec<DirectoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorSubscription {
+    pub id: String,
+    pub root: String,
+    pub target: PathBuf,
+    pub interval_secs: u64,
+    pub remove_stale: bool,
+    pub last_summary: Option<MirrorSummary>,
+}
+
+// Periodic mirror tasks, keyed by subscription id
+static SUBSCRIPTION_TASKS: Lazy<Mutex<HashMap<String, JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+// Serializes read-modify-write of the subscriptions file between commands and mirror tasks
+static SUBSCRIPTIONS_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn subscriptions_path(app_handle: &AppHandle) -> Result<PathBuf, StorageError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(SUBSCRIPTIONS_FILE))
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+pub async fn load_subscriptions(app_handle: &AppHandle) -> Vec<MirrorSubscription> {
+    let _guard = SUBSCRIPTIONS_LOCK.lock().await;
+    read_subscriptions(app_handle)
+}
+
+fn read_subscriptions(app_handle: &AppHandle) -> Vec<MirrorSubscription> {
+    match subscriptions_path(app_handle) {
+        Ok(path) => load_json(&path),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Applies `change` to the stored subscriptions while holding the lock
+async fn update_subscriptions(
+    app_handle: &AppHandle,
+    change: impl FnOnce(&mut Vec<MirrorSubscription>),
+) -> Result<(), StorageError> {
+    let _guard = SUBSCRIPTIONS_LOCK.lock().await;
+    let mut subscriptions = read_subscriptions(app_handle);
+    change(&mut subscriptions);
+    save_json(&subscriptions_path(app_handle)?, &subscriptions)
+}
+
+fn sha256_file(path: &Path) -> Result<String, StorageError> {
+    let mut file = std::fs::File::open(path).map_err(|e| StorageError::Io(e.to_string()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Resolves a manifest path inside `target`, refusing anything that could escape it
+fn entry_path(target: &Path, relative: &str) -> Result<PathBuf, StorageError> {
+    let relative = Path::new(relative);
+    let is_safe = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !is_safe || relative.as_os_str().is_empty() {
+        return Err(StorageError::Download(format!(
+            "Refusing unsafe path in directory manifest: {}",
+            relative.display()
+        )));
+    }
+    Ok(target.join(relative))
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    path.with_file_name(format!(
+        ".{}.{}.part",
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        Uuid::new_v4()
+    ))
+}
+
+/// Uploads every file of a folder and then the directory manifest listing them
+pub async fn publish_directory_with_handle(
+    path: PathBuf,
+    app_handle: AppHandle,
+) -> Result<PublishedDirectory, StorageError> {
+    if !path.is_dir() {
+        return Err(StorageError::FileNotFound(
+            path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let mut files = Vec::new();
+    collect_files(&path, &mut files);
+    files.sort();
+
+    let mut entries = Vec::with_capacity(files.len());
+    let mut total_size = 0;
+    for file in files {
+        let relative = file
+            .strip_prefix(&path)
+            .map_err(|e| StorageError::Io(e.to_string()))?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let hash_path = file.clone();
+        let sha256 = tokio::task::spawn_blocking(move || sha256_file(&hash_path))
+            .await
+            .map_err(|e| StorageError::Upload(e.to_string()))??;
+        let result = upload_file_with_progress(file, app_handle.clone()).await?;
+
+        total_size += result.size as u64;
+        entries.push(DirectoryEntry {
+            path: relative,
+            cid: result.cid,
+            size: result.size as u64,
+            sha256,
+        });
+    }
+
+    let manifest = DirectoryManifest {
+        version: DIRECTORY_MANIFEST_VERSION,
+        entries,
+    };
+    let contents = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let temp_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join("uploads");
+    std::fs::create_dir_all(&temp_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    let manifest_path = temp_dir.join(format!("{}.json", Uuid::new_v4()));
+    std::fs::write(&manifest_path, contents).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let result = upload_file_with_progress(manifest_path.clone(), app_handle).await;
+    let _ = std::fs::remove_file(&manifest_path);
+
+    Ok(PublishedDirectory {
+        cid: result?.cid,
+        files: manifest.entries.len(),
+        total_size,
+    })
+}
+
+async fn fetch_directory_manifest(
+    root_cid: &str,
+    app_handle: &AppHandle,
+) -> Result<DirectoryManifest, StorageError> {
+    let temp_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    std::fs::create_dir_all(&temp_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    let manifest_path = temp_dir.join(format!("{}.json", Uuid::new_v4()));
+
+    let result = download_file_with_progress(
+        root_cid.to_string(),
+        manifest_path.clone(),
+        app_handle.clone(),
+    )
+    .await
+    .and_then(|_| std::fs::read(&manifest_path).map_err(|e| StorageError::Io(e.to_string())));
+    let _ = std::fs::remove_file(&manifest_path);
+
+    let manifest: DirectoryManifest = serde_json::from_slice(&result?).map_err(|e| {
+        StorageError::Download(format!("{} is not a directory manifest: {}", root_cid, e))
+    })?;
+    if manifest.version != DIRECTORY_MANIFEST_VERSION {
+        return Err(StorageError::Download(format!(
+            "Unsupported directory manifest version {}",
+            manifest.version
+        )));
+    }
+
+    Ok(manifest)
+}
+
+/// Brings `target` in line with the directory published under `root`.
+///
+/// `root` is a CID or a name address resolved to its latest CID first. Files whose
+/// local hash already matches are skipped, and files that are no longer listed are
+/// only deleted when `remove_stale` is set.
+pub async fn mirror_directory_with_handle(
+    root: String,
+    target: PathBuf,
+    remove_stale: bool,
+    app_handle: AppHandle,
+) -> Result<MirrorSummary, StorageError> {
+    let root_cid = if is_name_address(&root) {
+        resolve_name_with_handle(root, app_handle.clone())
+            .await?
+            .cid
+    } else {
+        root
+    };
+    let manifest = fetch_directory_manifest(&root_cid, &app_handle).await?;
+    std::fs::create_dir_all(&target).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let mut summary = MirrorSummary {
+        root_cid,
+        ..Default::default()
+    };
+    let mut listed = HashSet::new();
+
+    for entry in &manifest.entries {
+        let path = match entry_path(&target, &entry.path) {
+            Ok(path) => path,
+            Err(_) => {
+                summary.failed.push(entry.path.clone());
+                continue;
+            }
+        };
+        listed.insert(path.clone());
+
+        let exists = path.is_file();
+        if exists {
+            let hash_path = path.clone();
+            let local_hash = tokio::task::spawn_blocking(move || sha256_file(&hash_path))
+                .await
+                .map_err(|e| StorageError::Download(e.to_string()))?;
+            if local_hash.map(|hash| hash == entry.sha256).unwrap_or(false) {
+                summary.unchanged += 1;
+                continue;
+            }
+        }
+
+        match mirror_entry(entry, &path, &app_handle).await {
+            Ok(()) if exists => summary.updated.push(entry.path.clone()),
+            Ok(()) => summary.added.push(entry.path.clone()),
+            Err(e) => {
+                error!("Failed to mirror {}: {}", entry.path, e);
+                summary.failed.push(entry.path.clone());
+            }
+        }
+    }
+
+    if remove_stale {
+        let canonical_target = target
+            .canonicalize()
+            .map_err(|e| StorageError::Io(e.to_string()))?;
+        let mut local = Vec::new();
+        collect_files(&target, &mut local);
+        for path in local.into_iter().filter(|path| !listed.contains(path)) {
+            // The listing skips symlinks, but the tree may have changed since
+            if !is_removable(&canonical_target, &path) {
+                warn!("Not removing {}, it is outside the mirror", path.display());
+                continue;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => summary.removed.push(
+                    path.strip_prefix(&target)
+                        .unwrap_or(&path)
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                Err(e) => error!("Failed to remove stale {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Whether `path` is a regular file that really lives inside `canonical_root`
+fn is_removable(canonical_root: &Path, path: &Path) -> bool {
+    let is_file = std::fs::symlink_metadata(path)
+        .map(|metadata| metadata.is_file())
+        .unwrap_or(false);
+    is_file
+        && path
+            .parent()
+            .and_then(|parent| parent.canonicalize().ok())
+            .is_some_and(|parent| parent.starts_with(canonical_root))
+}
+
+async fn mirror_entry(
+    entry: &DirectoryEntry,
+    path: &Path,
+    app_handle: &AppHandle,
+) -> Result<(), StorageError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+
+    // Download beside the destination and only replace it once the hash checks out
+    let temp_path = temp_path_for(path);
+    let result = async {
+        download_file_with_progress(entry.cid.clone(), temp_path.clone(), app_handle.clone())
+            .await?;
+
+        let hash_path = temp_path.clone();
+        let hash = tokio::task::spawn_blocking(move || sha256_file(&hash_path))
+            .await
+            .map_err(|e| StorageError::Download(e.to_string()))??;
+        if hash != entry.sha256 {
+            return Err(StorageError::Download(format!(
+                "Hash mismatch for {}",
+                entry.path
+            )));
+        }
+
+        std::fs::rename(&temp_path, path).map_err(|e| StorageError::Io(e.to_string()))
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Persists a periodic mirror and starts running it
+pub async fn subscribe_directory_with_handle(
+    root: String,
+    target: PathBuf,
+    interval_secs: u64,
+    remove_stale: bool,
+    app_handle: AppHandle,
+) -> Result<MirrorSubscription, StorageError> {
+    if interval_secs == 0 {
+        return Err(StorageError::Configuration(
+            "Mirror interval must be at least one second".to_string(),
+        ));
+    }
+
+    let subscription = MirrorSubscription {
+        id: Uuid::new_v4().to_string(),
+        root,
+        target,
+        interval_secs,
+        remove_stale,
+        last_summary: None,
+    };
+
+    update_subscriptions(&app_handle, |subscriptions| {
+        subscriptions.push(subscription.clone())
+    })
+    .await?;
+
+    start_subscription(subscription.clone(), app_handle).await;
+    Ok(subscription)
+}
+
+pub async fn unsubscribe_directory_with_handle(
+    id: String,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    if let Some(task) = SUBSCRIPTION_TASKS.lock().await.remove(&id) {
+        task.abort();
+    }
+
+    update_subscriptions(&app_handle, |subscriptions| {
+        subscriptions.retain(|subscription| subscription.id != id)
+    })
+    .await
+}
+
+/// Restarts every persisted subscription, used on app startup
+pub async fn restore_subscriptions(app_handle: AppHandle) {
+    for subscription in load_subscriptions(&app_handle).await {
+        start_subscription(subscription, app_handle.clone()).await;
+    }
+}
+
+async fn start_subscription(subscription: MirrorSubscription, app_handle: AppHandle) {
+    let id = subscription.id.clone();
+    let task = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(subscription.interval_secs));
+        loop {
+            interval.tick().await;
+            if transfers_paused() {
+                continue;
+            }
+
+            let summary = match mirror_directory_with_handle(
+                subscription.root.clone(),
+                subscription.target.clone(),
+                subscription.remove_stale,
+                app_handle.clone(),
+            )
+            .await
+            {
+                Ok(summary) => summary,
+                Err(e) => {
+                    error!("Mirror of {} failed: {}", subscription.root, e);
+                    continue;
+                }
+            };
+
+            let updated = update_subscriptions(&app_handle, |subscriptions| {
+                if let Some(stored) = subscriptions
+                    .iter_mut()
+                    .find(|stored| stored.id == subscription.id)
+                {
+                    stored.last_summary = Some(summary);
+                }
+            })
+            .await;
+            if let Err(e) = updated {
+                error!("Failed to save subscriptions: {}", e);
+            }
+        }
+    });
+
+    if let Some(previous) = SUBSCRIPTION_TASKS.lock().await.insert(id, task) {
+        previous.abort();
+    }
+}
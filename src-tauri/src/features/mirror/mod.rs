@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod mirror;
+
+pub use commands::*;
+pub use mirror::*;
@@ -1,4 +1,25 @@
+pub mod bootstrap;
+pub mod compression;
 pub mod connection;
+pub mod diagnostics;
 pub mod download;
+pub mod encryption;
+pub mod gateway;
+pub mod identity;
+pub mod instance;
+pub mod lan;
+pub mod logging;
+pub mod maintenance;
+pub mod metrics;
+pub mod mirror;
+pub mod naming;
+pub mod peers;
+pub mod profiles;
+pub mod reachability;
+pub mod settings;
 pub mod shared;
+pub mod shutdown;
+#[cfg(desktop)]
+pub mod tray;
 pub mod upload;
+pub mod watch;
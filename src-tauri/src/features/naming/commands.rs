@@ -0,0 +1,28 @@
+use crate::features::naming::{
+    naming_public_key, publish_name_with_handle, resolve_name_with_handle,
+};
+use crate::features::shared::{map_storage_error, NameResolution};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn get_naming_key(app_handle: AppHandle) -> Result<String, String> {
+    naming_public_key(&app_handle).map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn publish_name(
+    name: String,
+    cid: String,
+    app_handle: AppHandle,
+) -> Result<NameResolution, String> {
+    publish_name_with_handle(name, cid, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn resolve_name(target: String, app_handle: AppHandle) -> Result<NameResolution, String> {
+    resolve_name_with_handle(target, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
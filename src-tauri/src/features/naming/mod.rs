@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod naming;
+
+pub use commands::*;
+pub use naming::*;
@@ -0,0 +1,484 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use codex_bindings::{dht_get_values, dht_put_value, CodexNode};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::download::download_file_with_progress;
+use crate::features::shared::{load_json, save_json, NameResolution, StorageError};
+use crate::features::upload::upload_file_with_progress;
+
+const KEY_FILE: &str = "naming.key";
+const REGISTRY_FILE: &str = "names.json";
+
+/// Domain separator so a naming signature can never be replayed as anything else
+const SIGNING_CONTEXT: &str = "storeman-name-v1";
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz234567";
+// Bounds of a multibase encoded CIDv1 with a sha2-256 multihash
+const MIN_CID_LEN: usize = 40;
+const MAX_CID_LEN: usize = 100;
+
+/// Names end up in the newline separated signing payload, so they may not contain
+/// control characters, and '/' separates them from the key in an address
+fn validate_name(name: &str) -> Result<(), StorageError> {
+    if name.is_empty() || name.contains('/') || name.chars().any(char::is_control) {
+        return Err(StorageError::InvalidNameRecord(
+            "Name must be non-empty and cannot contain '/' or control characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Accepts base58btc (`z`) and base32 (`b`) multibase CIDs, the encodings the node uses
+fn validate_cid(cid: &str) -> Result<(), StorageError> {
+    let alphabet = match cid.chars().next() {
+        Some('z') => BASE58_ALPHABET,
+        Some('b') => BASE32_ALPHABET,
+        _ => "",
+    };
+    let is_valid = !alphabet.is_empty()
+        && (MIN_CID_LEN..=MAX_CID_LEN).contains(&cid.len())
+        && cid[1..].chars().all(|c| alphabet.contains(c));
+    if !is_valid {
+        return Err(StorageError::InvalidCid(cid.to_string()));
+    }
+    Ok(())
+}
+
+/// Signed mapping from a name to a CID, uploaded as content itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameRecord {
+    pub public_key: String,
+    pub name: String,
+    pub cid: String,
+    pub sequence: u64,
+    pub signature: String,
+}
+
+impl NameRecord {
+    /// The stable address of a name, independent of the CID it points to
+    pub fn address(&self) -> String {
+        format!("{}/{}", self.public_key, self.name)
+    }
+
+    fn signing_payload(public_key: &str, name: &str, cid: &str, sequence: u64) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}\n{}",
+            SIGNING_CONTEXT, public_key, name, sequence, cid
+        )
+        .into_bytes()
+    }
+
+    pub fn verify(&self) -> Result<(), StorageError> {
+        validate_name(&self.name)?;
+        validate_cid(&self.cid)?;
+
+        let public_key: [u8; 32] = URL_SAFE_NO_PAD
+            .decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| StorageError::InvalidNameRecord("Malformed public key".to_string()))?;
+        let signature: [u8; 64] = URL_SAFE_NO_PAD
+            .decode(&self.signature)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| StorageError::InvalidNameRecord("Malformed signature".to_string()))?;
+
+        let verifying_key = VerifyingKey::from_bytes(&public_key)
+            .map_err(|e| StorageError::InvalidNameRecord(e.to_string()))?;
+        let payload = Self::signing_payload(&self.public_key, &self.name, &self.cid, self.sequence);
+        verifying_key
+            .verify(&payload, &Signature::from_bytes(&signature))
+            .map_err(|_| StorageError::InvalidNameRecord("Signature does not match".to_string()))
+    }
+}
+
+/// Also the value stored in the DHT under the name address, the record inside is
+/// what gets verified, `record_cid` only points at the uploaded copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegistryEntry {
+    record: NameRecord,
+    record_cid: String,
+}
+
+/// Latest known record per name address, for both our own and resolved names
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct NameRegistry {
+    names: HashMap<String, RegistryEntry>,
+}
+
+fn data_file(app_handle: &AppHandle, file: &str) -> Result<PathBuf, StorageError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(file))
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+fn load_registry(app_handle: &AppHandle) -> NameRegistry {
+    match data_file(app_handle, REGISTRY_FILE) {
+        Ok(path) => load_json(&path),
+        Err(_) => NameRegistry::default(),
+    }
+}
+
+fn save_registry(app_handle: &AppHandle, registry: &NameRegistry) -> Result<(), StorageError> {
+    save_json(&data_file(app_handle, REGISTRY_FILE)?, registry)
+}
+
+/// Loads the naming key pair, creating it on first use
+fn load_or_create_signing_key(app_handle: &AppHandle) -> Result<SigningKey, StorageError> {
+    let path = data_file(app_handle, KEY_FILE)?;
+
+    if let Ok(bytes) = std::fs::read(&path) {
+        let secret: [u8; 32] = bytes.try_into().map_err(|_| {
+            StorageError::Configuration(format!("Naming key {} is corrupted", path.display()))
+        })?;
+        return Ok(SigningKey::from_bytes(&secret));
+    }
+
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+    std::fs::write(&path, secret).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+
+    Ok(SigningKey::from_bytes(&secret))
+}
+
+/// Returns the public key names published from this install are signed with
+pub fn naming_public_key(app_handle: &AppHandle) -> Result<String, StorageError> {
+    let signing_key = load_or_create_signing_key(app_handle)?;
+    Ok(URL_SAFE_NO_PAD.encode(signing_key.verifying_key().to_bytes()))
+}
+
+/// Returns whether `value` is a name address rather than a plain CID
+pub fn is_name_address(value: &str) -> bool {
+    value.contains('/')
+}
+
+/// DHT key a name address is published under, derived from the public key and name
+fn dht_key(address: &str) -> Vec<u8> {
+    Sha256::digest(format!("{}/{}", SIGNING_CONTEXT, address).as_bytes()).to_vec()
+}
+
+async fn started_node(app_handle: &AppHandle) -> Result<CodexNode, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let node = manager.get_node().await?;
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+    Ok(node)
+}
+
+/// Stores the entry in the DHT so other nodes can look the name up by its address
+async fn announce_name(node: &CodexNode, entry: &RegistryEntry) -> Result<(), StorageError> {
+    let value =
+        serde_json::to_vec(entry).map_err(|e| StorageError::Configuration(e.to_string()))?;
+    dht_put_value(node, &dht_key(&entry.record.address()), value)
+        .await
+        .map_err(|e| StorageError::InvalidNameRecord(format!("Failed to publish name: {}", e)))
+}
+
+/// Returns the verified entry with the highest sequence the DHT holds for `address`.
+///
+/// Values that do not parse, fail their signature or belong to another address are
+/// ignored, anyone can write under a key but only the key holder can sign for it.
+async fn lookup_name(
+    node: &CodexNode,
+    address: &str,
+) -> Result<Option<RegistryEntry>, StorageError> {
+    let values = dht_get_values(node, &dht_key(address))
+        .await
+        .map_err(|e| StorageError::InvalidNameRecord(format!("Failed to look up name: {}", e)))?;
+
+    Ok(values
+        .iter()
+        .filter_map(|value| serde_json::from_slice::<RegistryEntry>(value).ok())
+        .filter(|entry| entry.record.address() == address && entry.record.verify().is_ok())
+        .max_by_key(|entry| entry.record.sequence))
+}
+
+/// Signs and uploads a new record pointing `name` at `cid`, then publishes it in the
+/// DHT under the name address
+pub async fn publish_name_with_handle(
+    name: String,
+    cid: String,
+    app_handle: AppHandle,
+) -> Result<NameResolution, StorageError> {
+    let name = name.trim().to_string();
+    validate_name(&name)?;
+    let cid = cid.trim().to_string();
+    validate_cid(&cid)?;
+
+    let node = started_node(&app_handle).await?;
+    let signing_key = load_or_create_signing_key(&app_handle)?;
+    let public_key = URL_SAFE_NO_PAD.encode(signing_key.verifying_key().to_bytes());
+    let address = format!("{}/{}", public_key, name);
+
+    // The network may know a later record, e.g. one published from a restored identity
+    let mut registry = load_registry(&app_handle);
+    let known = registry
+        .names
+        .get(&address)
+        .map(|entry| entry.record.sequence)
+        .unwrap_or(0);
+    let announced = match lookup_name(&node, &address).await {
+        Ok(entry) => entry.map(|entry| entry.record.sequence).unwrap_or(0),
+        Err(e) => {
+            warn!("Publishing {} without checking the DHT: {}", address, e);
+            0
+        }
+    };
+    let sequence = known.max(announced) + 1;
+
+    let payload = NameRecord::signing_payload(&public_key, &name, &cid, sequence);
+    let record = NameRecord {
+        public_key,
+        name,
+        cid,
+        sequence,
+        signature: URL_SAFE_NO_PAD.encode(signing_key.sign(&payload).to_bytes()),
+    };
+
+    let temp_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join("uploads");
+    std::fs::create_dir_all(&temp_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    let record_path = temp_dir.join(format!("{}.json", Uuid::new_v4()));
+    let contents = serde_json::to_vec_pretty(&record)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    std::fs::write(&record_path, contents).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let upload = upload_file_with_progress(record_path.clone(), app_handle.clone()).await;
+    let _ = std::fs::remove_file(&record_path);
+    let record_cid = upload?.cid;
+
+    let resolution = NameResolution {
+        address: address.clone(),
+        cid: record.cid.clone(),
+        sequence: record.sequence,
+        record_cid: record_cid.clone(),
+    };
+    let entry = RegistryEntry { record, record_cid };
+    announce_name(&node, &entry).await?;
+    registry.names.insert(address, entry);
+    save_registry(&app_handle, &registry)?;
+
+    Ok(resolution)
+}
+
+fn resolution(address: String, entry: &RegistryEntry) -> NameResolution {
+    NameResolution {
+        address,
+        cid: entry.record.cid.clone(),
+        sequence: entry.record.sequence,
+        record_cid: entry.record_cid.clone(),
+    }
+}
+
+/// Looks a name address up in the DHT and keeps whichever of the network and local
+/// records is newer. A local record the network lost or only has an older version of
+/// is published again, so names stay resolvable while anyone who knows them is online.
+async fn resolve_address(
+    address: String,
+    app_handle: &AppHandle,
+) -> Result<NameResolution, StorageError> {
+    let mut registry = load_registry(app_handle);
+    let local = registry
+        .names
+        .get(&address)
+        .filter(|entry| entry.record.verify().is_ok())
+        .cloned();
+
+    let node = started_node(app_handle).await;
+    let network = match &node {
+        Ok(node) => lookup_name(node, &address).await,
+        Err(_) => Err(StorageError::NodeNotStarted),
+    };
+
+    let network = match network {
+        Ok(network) => network,
+        Err(e) => {
+            // Offline the last known record is still the best answer
+            let entry = local.ok_or(e)?;
+            warn!("Resolved {} from the local registry only", address);
+            return Ok(resolution(address, &entry));
+        }
+    };
+
+    let network_sequence = network.as_ref().map(|entry| entry.record.sequence);
+    let latest = match (local, network) {
+        (Some(local), Some(network)) if local.record.sequence >= network.record.sequence => local,
+        (_, Some(network)) => network,
+        (Some(local), None) => local,
+        (None, None) => {
+            return Err(StorageError::InvalidNameRecord(format!(
+                "No record published for {}",
+                address
+            )))
+        }
+    };
+
+    if network_sequence < Some(latest.record.sequence) {
+        if let Ok(node) = &node {
+            match announce_name(node, &latest).await {
+                Ok(()) => info!(
+                    "Republished {} at sequence {}",
+                    address, latest.record.sequence
+                ),
+                Err(e) => warn!("Failed to republish {}: {}", address, e),
+            }
+        }
+    }
+
+    let known = registry
+        .names
+        .get(&address)
+        .map(|entry| entry.record.sequence);
+    if known < Some(latest.record.sequence) {
+        registry.names.insert(address.clone(), latest.clone());
+        save_registry(app_handle, &registry)?;
+    }
+
+    Ok(resolution(address, &latest))
+}
+
+async fn fetch_name_record(
+    record_cid: &str,
+    app_handle: &AppHandle,
+) -> Result<NameRecord, StorageError> {
+    let temp_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    std::fs::create_dir_all(&temp_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    let record_path = temp_dir.join(format!("{}.json", Uuid::new_v4()));
+
+    let result = download_file_with_progress(
+        record_cid.to_string(),
+        record_path.clone(),
+        app_handle.clone(),
+    )
+    .await
+    .and_then(|_| std::fs::read(&record_path).map_err(|e| StorageError::Io(e.to_string())));
+    let _ = std::fs::remove_file(&record_path);
+
+    serde_json::from_slice(&result?).map_err(|e| StorageError::InvalidNameRecord(e.to_string()))
+}
+
+/// Resolves a name to its latest CID.
+///
+/// `target` is either a name address, looked up in the DHT, or the CID of a record,
+/// which is fetched and verified and replaces the known record when its sequence
+/// number is higher.
+pub async fn resolve_name_with_handle(
+    target: String,
+    app_handle: AppHandle,
+) -> Result<NameResolution, StorageError> {
+    if is_name_address(&target) {
+        return resolve_address(target, &app_handle).await;
+    }
+
+    let mut registry = load_registry(&app_handle);
+    let record = fetch_name_record(&target, &app_handle).await?;
+    record.verify()?;
+
+    let address = record.address();
+    let is_newer = registry
+        .names
+        .get(&address)
+        .map(|known| record.sequence > known.record.sequence)
+        .unwrap_or(true);
+    if is_newer {
+        registry.names.insert(
+            address.clone(),
+            RegistryEntry {
+                record,
+                record_cid: target,
+            },
+        );
+        save_registry(&app_handle, &registry)?;
+    }
+
+    // An older record never rolls the name back
+    let latest = &registry.names[&address];
+    Ok(resolution(address, latest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CID: &str = "zDvZRwzmAkhzDRPH5EW242gJBNZ2T7aoH2v1fVH66FxXL4kSbvyM";
+
+    fn signed(name: &str, cid: &str, sequence: u64) -> NameRecord {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let public_key = URL_SAFE_NO_PAD.encode(signing_key.verifying_key().to_bytes());
+        let payload = NameRecord::signing_payload(&public_key, name, cid, sequence);
+        NameRecord {
+            public_key,
+            name: name.to_string(),
+            cid: cid.to_string(),
+            sequence,
+            signature: URL_SAFE_NO_PAD.encode(signing_key.sign(&payload).to_bytes()),
+        }
+    }
+
+    #[test]
+    fn verifies_signed_record() {
+        assert!(signed("site", CID, 3).verify().is_ok());
+    }
+
+    #[test]
+    fn rejects_tampered_sequence() {
+        let mut record = signed("site", CID, 3);
+        record.sequence = 4;
+        assert!(record.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_name() {
+        let mut record = signed("site", CID, 3);
+        record.name = "other".to_string();
+        assert!(record.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_cid() {
+        let mut record = signed("site", CID, 3);
+        record.cid = CID.replace('R', "S");
+        assert!(record.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_control_characters_and_malformed_cids() {
+        // Signed correctly, but the payload fields could be shifted across lines
+        assert!(signed("site\n3", CID, 3).verify().is_err());
+        assert!(signed("site", "not a cid", 3).verify().is_err());
+        assert!(validate_cid(&CID.replace('z', "0")).is_err());
+        assert!(validate_cid("").is_err());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("a/b").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::features::peers::{
+    ban_peer_with_handle, banned_peers, disconnect_peer_with_handle,
+    list_connected_peers_with_handle, unban_peer_with_handle,
+};
+use crate::features::shared::{map_storage_error, BannedPeer, ConnectedPeer};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn list_connected_peers(app_handle: AppHandle) -> Result<Vec<ConnectedPeer>, String> {
+    list_connected_peers_with_handle(app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn disconnect_peer(peer_id: String, app_handle: AppHandle) -> Result<(), String> {
+    disconnect_peer_with_handle(peer_id, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn ban_peer(
+    peer_id: String,
+    reason: Option<String>,
+    app_handle: AppHandle,
+) -> Result<BannedPeer, String> {
+    ban_peer_with_handle(peer_id, reason, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn unban_peer(peer_id: String, app_handle: AppHandle) -> Result<(), String> {
+    unban_peer_with_handle(peer_id, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn list_banned_peers(app_handle: AppHandle) -> Result<Vec<BannedPeer>, String> {
+    Ok(banned_peers(&app_handle).await)
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod peers;
+
+pub use commands::*;
+pub use peers::*;
@@ -0,0 +1,247 @@
+use codex_bindings::{connected_peers, disconnect, ping, CodexNode, Direction};
+use futures_util::future::join_all;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::shared::{
+    load_json, save_json, BannedPeer, ConnectedPeer, PeerDirection, StorageError,
+};
+
+const BAN_LIST_FILE: &str = "banned_peers.json";
+
+// Banned peers that reconnect are dropped again within this interval
+const ENFORCE_INTERVAL: Duration = Duration::from_secs(10);
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BanList {
+    peers: Vec<BannedPeer>,
+}
+
+#[derive(Default)]
+struct PeerState {
+    bans: Option<BanList>,
+    /// When each currently connected peer was first seen
+    first_seen: HashMap<String, Instant>,
+}
+
+static PEER_STATE: Lazy<Mutex<PeerState>> = Lazy::new(|| Mutex::new(PeerState::default()));
+static ENFORCE_TASK: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+fn ban_list_path(app_handle: &AppHandle) -> Result<PathBuf, StorageError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(BAN_LIST_FILE))
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+fn load_ban_list(app_handle: &AppHandle) -> BanList {
+    match ban_list_path(app_handle) {
+        Ok(path) => load_json(&path),
+        Err(_) => BanList::default(),
+    }
+}
+
+fn save_ban_list(app_handle: &AppHandle, bans: &BanList) -> Result<(), StorageError> {
+    save_json(&ban_list_path(app_handle)?, bans)
+}
+
+/// Returns the ban list, loading it from disk on first use
+async fn bans(app_handle: &AppHandle) -> BanList {
+    let mut state = PEER_STATE.lock().await;
+    state
+        .bans
+        .get_or_insert_with(|| load_ban_list(app_handle))
+        .clone()
+}
+
+pub async fn is_peer_banned(peer_id: &str, app_handle: &AppHandle) -> bool {
+    bans(app_handle)
+        .await
+        .peers
+        .iter()
+        .any(|banned| banned.peer_id == peer_id)
+}
+
+async fn started_node(app_handle: &AppHandle) -> Result<CodexNode, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let node = manager.get_node().await?;
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+    Ok(node)
+}
+
+async fn measure_latency(node: &CodexNode, peer_id: &str) -> Option<u64> {
+    match tokio::time::timeout(PING_TIMEOUT, ping(node, peer_id)).await {
+        Ok(Ok(rtt)) => Some(rtt.as_millis() as u64),
+        _ => None,
+    }
+}
+
+/// Lists the peers the node has an open connection to, with a fresh latency sample
+pub async fn list_connected_peers_with_handle(
+    app_handle: AppHandle,
+) -> Result<Vec<ConnectedPeer>, StorageError> {
+    let node = started_node(&app_handle).await?;
+    let connections = connected_peers(&node)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let ages: HashMap<String, u64> = {
+        let mut state = PEER_STATE.lock().await;
+        state
+            .first_seen
+            .retain(|peer_id, _| connections.iter().any(|peer| &peer.peer_id == peer_id));
+        connections
+            .iter()
+            .map(|peer| {
+                let first_seen = *state
+                    .first_seen
+                    .entry(peer.peer_id.clone())
+                    .or_insert_with(Instant::now);
+                (peer.peer_id.clone(), first_seen.elapsed().as_secs())
+            })
+            .collect()
+    };
+
+    // Ping every peer at once so one slow peer does not hold up the list
+    let latencies = join_all(
+        connections
+            .iter()
+            .map(|connection| measure_latency(&node, &connection.peer_id)),
+    )
+    .await;
+
+    let mut peers = Vec::with_capacity(connections.len());
+    for (connection, latency_ms) in connections.into_iter().zip(latencies) {
+        peers.push(ConnectedPeer {
+            observed_secs: ages.get(&connection.peer_id).copied().unwrap_or(0),
+            direction: match connection.direction {
+                Some(Direction::Inbound) => PeerDirection::Inbound,
+                Some(Direction::Outbound) => PeerDirection::Outbound,
+                None => PeerDirection::Unknown,
+            },
+            peer_id: connection.peer_id,
+            addresses: connection.addresses,
+            latency_ms,
+        });
+    }
+
+    Ok(peers)
+}
+
+pub async fn disconnect_peer_with_handle(
+    peer_id: String,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let node = started_node(&app_handle).await?;
+    disconnect(&node, &peer_id)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    PEER_STATE.lock().await.first_seen.remove(&peer_id);
+    Ok(())
+}
+
+/// Adds a peer to the persisted ban list and drops its connection if there is one
+pub async fn ban_peer_with_handle(
+    peer_id: String,
+    reason: Option<String>,
+    app_handle: AppHandle,
+) -> Result<BannedPeer, StorageError> {
+    if peer_id.trim().is_empty() {
+        return Err(StorageError::Configuration(
+            "Peer ID cannot be empty".to_string(),
+        ));
+    }
+
+    let banned = BannedPeer {
+        peer_id: peer_id.clone(),
+        reason,
+        banned_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+    };
+
+    {
+        let mut state = PEER_STATE.lock().await;
+        let bans = state.bans.get_or_insert_with(|| load_ban_list(&app_handle));
+        bans.peers.retain(|existing| existing.peer_id != peer_id);
+        bans.peers.push(banned.clone());
+        save_ban_list(&app_handle, bans)?;
+    }
+
+    if let Ok(node) = started_node(&app_handle).await {
+        if let Err(e) = disconnect(&node, &peer_id).await {
+            warn!("Failed to disconnect banned peer {}: {}", peer_id, e);
+        }
+    }
+
+    Ok(banned)
+}
+
+pub async fn unban_peer_with_handle(
+    peer_id: String,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let mut state = PEER_STATE.lock().await;
+    let bans = state.bans.get_or_insert_with(|| load_ban_list(&app_handle));
+    bans.peers.retain(|existing| existing.peer_id != peer_id);
+    save_ban_list(&app_handle, bans)
+}
+
+pub async fn banned_peers(app_handle: &AppHandle) -> Vec<BannedPeer> {
+    bans(app_handle).await.peers
+}
+
+/// Periodically drops connections from banned peers.
+///
+/// The node has no way to refuse a peer up front, so banned peers that dial in
+/// are disconnected as soon as they show up.
+pub async fn start_ban_enforcement(app_handle: AppHandle) {
+    let mut task = ENFORCE_TASK.lock().await;
+    if task.is_some() {
+        return;
+    }
+
+    *task = Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ENFORCE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let banned = bans(&app_handle).await.peers;
+            if banned.is_empty() {
+                continue;
+            }
+            let Ok(node) = started_node(&app_handle).await else {
+                continue;
+            };
+            let Ok(connections) = connected_peers(&node).await else {
+                continue;
+            };
+
+            for connection in connections {
+                if banned
+                    .iter()
+                    .any(|banned| banned.peer_id == connection.peer_id)
+                {
+                    info!(
+                        "Dropping connection from banned peer {}",
+                        connection.peer_id
+                    );
+                    let _ = disconnect(&node, &connection.peer_id).await;
+                }
+            }
+        }
+    }));
+}
@@ -0,0 +1,51 @@
+use crate::features::profiles::{
+    create_node_profile, delete_node_profile, list_node_profiles, switch_node_profile,
+    update_node_profile,
+};
+use crate::features::settings::{load_settings, NodeProfile};
+use crate::features::shared::map_storage_error;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn list_profiles(app_handle: AppHandle) -> Result<Vec<NodeProfile>, String> {
+    Ok(list_node_profiles(&app_handle))
+}
+
+#[tauri::command]
+pub async fn get_active_profile(app_handle: AppHandle) -> Result<NodeProfile, String> {
+    Ok(load_settings(&app_handle).active_profile())
+}
+
+#[tauri::command]
+pub async fn create_profile(
+    profile: NodeProfile,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, String> {
+    create_node_profile(profile, &app_handle).map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn update_profile(
+    profile: NodeProfile,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, String> {
+    update_node_profile(profile, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn delete_profile(
+    name: String,
+    remove_data: bool,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    delete_node_profile(&name, remove_data, &app_handle).map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn switch_profile(name: String, app_handle: AppHandle) -> Result<NodeProfile, String> {
+    switch_node_profile(name, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
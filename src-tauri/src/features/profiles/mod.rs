@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod profiles;
+
+pub use commands::*;
+pub use profiles::*;
@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::features::bootstrap::validate_bootstrap;
+use crate::features::connection::{
+    create_codex_config_for_profile, get_storage_manager_with_handle, profile_data_dir,
+    validate_network_settings,
+};
+use crate::features::lan::apply_lan_mode;
+use crate::features::settings::{load_settings, save_settings, NodeProfile, DEFAULT_PROFILE};
+use crate::features::shared::StorageError;
+
+pub fn validate_profile_name(name: &str) -> Result<(), StorageError> {
+    let is_valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !is_valid {
+        return Err(StorageError::Configuration(format!(
+            "Invalid profile name '{}', use letters, digits, '-' and '_' only",
+            name
+        )));
+    }
+    Ok(())
+}
+
+pub fn list_node_profiles(app_handle: &AppHandle) -> Vec<NodeProfile> {
+    load_settings(app_handle).profiles
+}
+
+pub fn create_node_profile(
+    profile: NodeProfile,
+    app_handle: &AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    validate_profile_name(&profile.name)?;
+    validate_bootstrap(&profile)?;
+    validate_network_settings(&profile)?;
+
+    let mut settings = load_settings(app_handle);
+    if settings
+        .profiles
+        .iter()
+        .any(|existing| existing.name == profile.name)
+    {
+        return Err(StorageError::Configuration(format!(
+            "Profile '{}' already exists",
+            profile.name
+        )));
+    }
+
+    settings.profiles.push(profile.clone());
+    save_settings(app_handle, &settings)?;
+    Ok(profile)
+}
+
+/// Updates a profile, restarting the node when it is the active one
+pub async fn update_node_profile(
+    profile: NodeProfile,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    validate_bootstrap(&profile)?;
+    validate_network_settings(&profile)?;
+
+    let mut settings = load_settings(&app_handle);
+    let existing = settings
+        .profiles
+        .iter_mut()
+        .find(|existing| existing.name == profile.name)
+        .ok_or_else(|| {
+            StorageError::Configuration(format!("Profile '{}' does not exist", profile.name))
+        })?;
+    if existing.repo_backend != profile.repo_backend {
+        return Err(StorageError::Configuration(
+            "The repository backend can only be changed by migrating the repository".to_string(),
+        ));
+    }
+    *existing = profile.clone();
+
+    // Only save once the node runs with the new settings, a refused or failed
+    // restart keeps the stored profile matching the node
+    let is_active = settings.active_profile == profile.name;
+    if is_active {
+        let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+        manager
+            .switch_config(create_codex_config_for_profile(&app_handle, &profile)?)
+            .await?;
+    }
+    save_settings(&app_handle, &settings)?;
+    if is_active {
+        apply_lan_mode(app_handle).await?;
+    }
+
+    Ok(profile)
+}
+
+/// Removes a profile and optionally its node data. The active and default profiles are kept.
+pub fn delete_node_profile(
+    name: &str,
+    remove_data: bool,
+    app_handle: &AppHandle,
+) -> Result<(), StorageError> {
+    validate_profile_name(name)?;
+    let mut settings = load_settings(app_handle);
+    if !settings.profiles.iter().any(|profile| profile.name == name) {
+        return Err(StorageError::Configuration(format!(
+            "Profile '{}' does not exist",
+            name
+        )));
+    }
+    if name == DEFAULT_PROFILE {
+        return Err(StorageError::Configuration(
+            "The default profile cannot be deleted".to_string(),
+        ));
+    }
+    if settings.active_profile == name {
+        return Err(StorageError::Configuration(
+            "Switch to another profile before deleting the active one".to_string(),
+        ));
+    }
+
+    // Resolve the folder before touching settings so a refused delete changes nothing
+    let profile_dir = if remove_data {
+        deletable_profile_dir(name, app_handle)?
+    } else {
+        None
+    };
+
+    settings.profiles.retain(|profile| profile.name != name);
+    save_settings(app_handle, &settings)?;
+
+    // Remove the whole profile folder, not only node_data
+    if let Some(profile_dir) = profile_dir {
+        std::fs::remove_dir_all(&profile_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the folder of a profile if it exists, refusing anything that does not
+/// resolve to a direct child of `profiles` in the app data directory, e.g. a symlink
+fn deletable_profile_dir(
+    name: &str,
+    app_handle: &AppHandle,
+) -> Result<Option<PathBuf>, StorageError> {
+    let profiles_root = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join("profiles");
+    let profile_dir = profiles_root.join(name);
+    if profile_data_dir(app_handle, name).parent() != Some(profile_dir.as_path()) {
+        return Err(StorageError::Configuration(format!(
+            "Profile '{}' does not keep its data in {}",
+            name,
+            profiles_root.display()
+        )));
+    }
+    if !profile_dir.exists() {
+        return Ok(None);
+    }
+
+    let canonical_root = profiles_root
+        .canonicalize()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    let canonical_dir = profile_dir
+        .canonicalize()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+    if canonical_dir.parent() != Some(canonical_root.as_path()) {
+        return Err(StorageError::Configuration(format!(
+            "Refusing to delete {}, it is outside {}",
+            canonical_dir.display(),
+            canonical_root.display()
+        )));
+    }
+    Ok(Some(canonical_dir))
+}
+
+/// Stops the current node, starts the one of `name` and remembers it as active
+pub async fn switch_node_profile(
+    name: String,
+    app_handle: AppHandle,
+) -> Result<NodeProfile, StorageError> {
+    let mut settings = load_settings(&app_handle);
+    let profile = settings
+        .profiles
+        .iter()
+        .find(|profile| profile.name == name)
+        .cloned()
+        .ok_or_else(|| StorageError::Configuration(format!("Profile '{}' does not exist", name)))?;
+
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    manager
+        .switch_config(create_codex_config_for_profile(&app_handle, &profile)?)
+        .await?;
+
+    settings.active_profile = name;
+    save_settings(&app_handle, &settings)?;
+    apply_lan_mode(app_handle).await?;
+
+    Ok(profile)
+}
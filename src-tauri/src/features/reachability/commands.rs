@@ -0,0 +1,10 @@
+use crate::features::reachability::check_reachability_with_handle;
+use crate::features::shared::{map_storage_error, ReachabilityReport};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn check_reachability(app_handle: AppHandle) -> Result<ReachabilityReport, String> {
+    check_reachability_with_handle(app_handle)
+        .await
+        .map_err(map_storage_error)
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod reachability;
+
+pub use commands::*;
+pub use reachability::*;
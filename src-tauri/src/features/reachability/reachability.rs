@@ -0,0 +1,202 @@
+use codex_bindings::{connect, debug, CodexConfig, CodexNode, LogLevel};
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::shared::{ReachabilityReport, StorageError};
+
+const DIAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Splits a multiaddr like `/ip4/1.2.3.4/tcp/8070` into its IP and TCP port
+fn tcp_endpoint(address: &str) -> Option<(IpAddr, u16)> {
+    let parts: Vec<&str> = address.split('/').collect();
+    match parts.as_slice() {
+        ["", "ip4" | "ip6", ip, "tcp", port, ..] => Some((ip.parse().ok()?, port.parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            let segment = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || (segment & 0xfe00) == 0xfc00
+                || (segment & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Rewrites the listen addresses so a node on the same machine can dial them
+fn loopback_addresses(listen_addrs: &[String]) -> Vec<String> {
+    listen_addrs
+        .iter()
+        .filter_map(|address| {
+            let (ip, port) = tcp_endpoint(address)?;
+            let ip = match ip {
+                IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+                ip => ip,
+            };
+            let protocol = if ip.is_ipv4() { "ip4" } else { "ip6" };
+            Some(format!("/{}/{}/tcp/{}", protocol, ip, port))
+        })
+        .collect()
+}
+
+/// Asks the system for a UDP port nothing is bound to
+fn free_udp_port() -> Result<u16, StorageError> {
+    std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|socket| socket.local_addr())
+        .map(|address| address.port())
+        .map_err(|e| StorageError::Io(e.to_string()))
+}
+
+/// Starts a throwaway node on loopback and has it dial `peer_id`.
+///
+/// This shows whether the node accepts inbound connections at all, it cannot
+/// tell whether a router in front of the machine forwards them.
+async fn loopback_dial(
+    peer_id: &str,
+    addresses: Vec<String>,
+    data_dir: &Path,
+) -> Result<(), StorageError> {
+    if addresses.is_empty() {
+        return Err(StorageError::Configuration(
+            "Node has no TCP listen address to dial".to_string(),
+        ));
+    }
+
+    let config = CodexConfig::new()
+        .log_level(LogLevel::Error)
+        .data_dir(data_dir)
+        .discovery_port(free_udp_port()?)
+        .listen_addrs(vec!["/ip4/127.0.0.1/tcp/0".to_string()])
+        .nat("none".to_string())
+        .bootstrap_nodes(Vec::new());
+    let mut probe =
+        CodexNode::new(config).map_err(|e| StorageError::NodeCreation(e.to_string()))?;
+    if let Err(e) = probe.start() {
+        let _ = probe.destroy();
+        return Err(StorageError::NodeStart(e.to_string()));
+    }
+
+    let result =
+        match tokio::time::timeout(DIAL_TIMEOUT, connect(&probe, peer_id, &addresses)).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(StorageError::Configuration(e.to_string())),
+            Err(_) => Err(StorageError::Configuration(format!(
+                "Dial timed out after {}s",
+                DIAL_TIMEOUT.as_secs()
+            ))),
+        };
+
+    if let Err(e) = probe.stop() {
+        warn!("Failed to stop reachability probe node: {}", e);
+    }
+    if let Err(e) = probe.destroy() {
+        warn!("Failed to destroy reachability probe node: {}", e);
+    }
+    result
+}
+
+/// Compares the listen and announce addresses of the running node and dials it
+/// from a second local node to see whether it accepts inbound connections
+pub async fn check_reachability_with_handle(
+    app_handle: AppHandle,
+) -> Result<ReachabilityReport, StorageError> {
+    let manager = get_storage_manager_with_handle(Some(app_handle.clone())).await?;
+    let node = manager.get_node().await?;
+    if !node.is_started() {
+        return Err(StorageError::NodeNotStarted);
+    }
+
+    let peer_id = node
+        .peer_id()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let info = debug(&node)
+        .await
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let listen_addrs = info.addrs;
+    let announce_addrs = info.announce_addresses;
+
+    let mut warnings = Vec::new();
+
+    let announced: Vec<(IpAddr, u16)> = announce_addrs
+        .iter()
+        .filter_map(|address| tcp_endpoint(address))
+        .collect();
+    let announces_public_address = announced.iter().any(|(ip, _)| is_public(*ip));
+    if announce_addrs.is_empty() {
+        warnings.push("The node announces no addresses, peers cannot dial it".to_string());
+    } else if !announces_public_address {
+        warnings.push(
+            "Only private addresses are announced, peers outside this network cannot dial the node"
+                .to_string(),
+        );
+    }
+
+    let listen_ports: HashSet<u16> = listen_addrs
+        .iter()
+        .filter_map(|address| tcp_endpoint(address))
+        .map(|(_, port)| port)
+        .collect();
+    let mismatched: Vec<u16> = announced
+        .iter()
+        .map(|(_, port)| *port)
+        .filter(|port| !listen_ports.contains(port))
+        .collect();
+    let announce_ports_match = mismatched.is_empty();
+    if !announce_ports_match {
+        warnings.push(format!(
+            "Announced ports {:?} are not listened on, they only work if the router forwards them",
+            mismatched
+        ));
+    }
+
+    let probe_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join(format!("reachability-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&probe_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+
+    let dial = loopback_dial(&peer_id, loopback_addresses(&listen_addrs), &probe_dir).await;
+    let _ = std::fs::remove_dir_all(&probe_dir);
+
+    let dial_error = dial.err().map(|e| e.to_string());
+    if let Some(e) = &dial_error {
+        warnings.push(format!("Loopback dial failed: {}", e));
+    }
+    info!(
+        "Reachability check finished, inbound {}, {} warning(s)",
+        dial_error.is_none(),
+        warnings.len()
+    );
+
+    Ok(ReachabilityReport {
+        listen_addrs,
+        announce_addrs,
+        announces_public_address,
+        announce_ports_match,
+        accepts_inbound: dial_error.is_none(),
+        dial_error,
+        warnings,
+    })
+}
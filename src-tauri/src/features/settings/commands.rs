@@ -0,0 +1,35 @@
+use crate::features::gateway::apply_gateway_settings;
+use crate::features::logging::apply_log_level;
+use crate::features::metrics::apply_metrics_settings;
+use crate::features::settings::{load_settings, save_settings, AppSettings};
+use crate::features::shared::map_storage_error;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn get_settings(app_handle: AppHandle) -> Result<AppSettings, String> {
+    Ok(load_settings(&app_handle))
+}
+
+/// Saves the app-wide sections of the settings. Profiles and the active profile are
+/// ignored here, they only change through the profile commands which validate them.
+#[tauri::command]
+pub async fn update_settings(settings: AppSettings, app_handle: AppHandle) -> Result<(), String> {
+    let mut stored = load_settings(&app_handle);
+    stored.background = settings.background;
+    stored.gateway = settings.gateway;
+    stored.logging = settings.logging;
+    stored.metrics = settings.metrics;
+    save_settings(&app_handle, &stored).map_err(map_storage_error)?;
+    #[cfg(desktop)]
+    crate::features::tray::apply_background_settings(&stored.background, &app_handle)
+        .map_err(map_storage_error)?;
+    apply_log_level(stored.logging.level)
+        .await
+        .map_err(map_storage_error)?;
+    apply_metrics_settings(&stored.metrics, app_handle.clone())
+        .await
+        .map_err(map_storage_error)?;
+    apply_gateway_settings(&stored.gateway, app_handle)
+        .await
+        .map_err(map_storage_error)
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::features::shared::{load_json, save_json, StorageError};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GatewaySettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for GatewaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8090,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevelSetting {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+/// Verbosity of both the app log and the node log
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    pub level: LogLevelSetting,
+}
+
+/// Desktop tray behaviour, ignored on mobile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    /// Closing the window hides it to the tray and keeps the node running
+    pub close_to_tray: bool,
+    pub launch_at_login: bool,
+    /// Start hidden in the tray when launched at login
+    pub start_minimized: bool,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            close_to_tray: true,
+            launch_at_login: false,
+            start_minimized: true,
+        }
+    }
+}
+
+/// Opt-in Prometheus endpoint, only ever bound to localhost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9464,
+        }
+    }
+}
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const DEFAULT_BOOTSTRAP_PRESET: &str = "default";
+
+/// Block store backends supported by the node
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoBackend {
+    #[default]
+    LevelDb,
+    Sqlite,
+    Fs,
+}
+
+/// A separate node identity with its own data directory, keys, ports and peer book
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NodeProfile {
+    pub name: String,
+    pub discovery_port: u16,
+    pub max_peers: u32,
+    pub storage_quota: u64,
+    /// Changed through a repository migration, never edited in place
+    pub repo_backend: RepoBackend,
+    /// Name of one of the bootstrap presets
+    pub bootstrap_preset: String,
+    /// Signed peer records used in addition to the preset
+    pub bootstrap_nodes: Vec<String>,
+    /// Skip public bootstrap and find peers on the local network instead
+    pub lan_mode: bool,
+    /// TCP port for peer connections, 0 lets the system pick one
+    pub listen_port: u16,
+    /// Listen multiaddrs, overriding `listen_port` when set
+    pub listen_addrs: Vec<String>,
+    /// Multiaddrs announced to other peers instead of the detected ones
+    pub announce_addrs: Vec<String>,
+    /// NAT traversal: "any", "none", "upnp", "pmp" or "extip:<ip>"
+    pub nat: String,
+    /// Move to a free port at startup instead of failing when a port is taken
+    pub port_fallback: bool,
+}
+
+impl Default for NodeProfile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.to_string(),
+            discovery_port: 8089,
+            max_peers: 50,
+            storage_quota: 1024 * 1024 * 1024, // 1 GB
+            repo_backend: RepoBackend::LevelDb,
+            bootstrap_preset: DEFAULT_BOOTSTRAP_PRESET.to_string(),
+            bootstrap_nodes: Vec::new(),
+            lan_mode: false,
+            listen_port: 0,
+            listen_addrs: Vec::new(),
+            announce_addrs: Vec::new(),
+            nat: "any".to_string(),
+            port_fallback: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub background: BackgroundSettings,
+    pub gateway: GatewaySettings,
+    pub logging: LoggingSettings,
+    pub metrics: MetricsSettings,
+    pub profiles: Vec<NodeProfile>,
+    pub active_profile: String,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            background: BackgroundSettings::default(),
+            gateway: GatewaySettings::default(),
+            logging: LoggingSettings::default(),
+            metrics: MetricsSettings::default(),
+            profiles: vec![NodeProfile::default()],
+            active_profile: DEFAULT_PROFILE.to_string(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Returns the active profile, falling back to the default one if it was removed
+    pub fn active_profile(&self) -> NodeProfile {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == self.active_profile)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+pub fn settings_path(app_handle: &AppHandle) -> Result<PathBuf, StorageError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+/// Loads the persisted settings, falling back to defaults when missing or unreadable.
+/// Unreadable settings are backed up first so the profiles in them are not lost.
+pub fn load_settings(app_handle: &AppHandle) -> AppSettings {
+    match settings_path(app_handle) {
+        Ok(path) => load_json(&path),
+        Err(_) => AppSettings::default(),
+    }
+}
+
+pub fn save_settings(app_handle: &AppHandle, settings: &AppSettings) -> Result<(), StorageError> {
+    save_json(&settings_path(app_handle)?, settings)
+}
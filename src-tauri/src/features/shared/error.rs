@@ -1,4 +1,11 @@
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+// Errors returned to the frontend, counted by kind for the metrics endpoint
+static ERROR_COUNTS: Lazy<Mutex<BTreeMap<&'static str, u64>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StorageError {
@@ -6,12 +13,23 @@ pub enum StorageError {
     NodeStart(String),
     NodeNotInitialized,
     NodeNotStarted,
+    NodeRunning,
     Upload(String),
     Download(String),
     FileNotFound(String),
     InvalidCid(String),
     Io(String),
     Configuration(String),
+    Decryption(String),
+    InvalidShare(String),
+    PassphraseRequired,
+    WrongPassphrase,
+    InvalidNameRecord(String),
+    PortInUse { port: u16 },
+    RepoLocked(String),
+    StaleRepoLock(String),
+    TransfersPaused,
+    ShuttingDown,
 }
 
 impl std::fmt::Display for StorageError {
@@ -21,19 +39,82 @@ impl std::fmt::Display for StorageError {
             StorageError::NodeStart(msg) => write!(f, "Failed to start node: {}", msg),
             StorageError::NodeNotInitialized => write!(f, "Node is not initialized"),
             StorageError::NodeNotStarted => write!(f, "Node is not started"),
+            StorageError::NodeRunning => write!(f, "Node is running, stop it first"),
             StorageError::Upload(msg) => write!(f, "Upload failed: {}", msg),
             StorageError::Download(msg) => write!(f, "Download failed: {}", msg),
             StorageError::FileNotFound(path) => write!(f, "File not found: {}", path),
             StorageError::InvalidCid(msg) => write!(f, "Invalid CID: {}", msg),
             StorageError::Io(msg) => write!(f, "IO error: {}", msg),
             StorageError::Configuration(msg) => write!(f, "Configuration error: {}", msg),
+            StorageError::Decryption(msg) => write!(f, "Decryption failed: {}", msg),
+            StorageError::InvalidShare(msg) => write!(f, "Invalid share: {}", msg),
+            StorageError::PassphraseRequired => {
+                write!(f, "Content is protected, a passphrase is required")
+            }
+            StorageError::WrongPassphrase => write!(f, "Wrong passphrase"),
+            StorageError::InvalidNameRecord(msg) => write!(f, "Invalid name record: {}", msg),
+            StorageError::PortInUse { port } => {
+                write!(f, "Port {} is already in use by another process", port)
+            }
+            StorageError::RepoLocked(path) => {
+                write!(f, "{} is in use by another storeman process", path)
+            }
+            StorageError::StaleRepoLock(path) => write!(
+                f,
+                "Repository {} has a stale lock from a previous run, clear it to continue",
+                path
+            ),
+            StorageError::TransfersPaused => write!(f, "Background transfers are paused"),
+            StorageError::ShuttingDown => write!(f, "The app is shutting down"),
+        }
+    }
+}
+
+impl StorageError {
+    /// Name of the variant, stable enough to be used as a metric label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StorageError::NodeCreation(_) => "NodeCreation",
+            StorageError::NodeStart(_) => "NodeStart",
+            StorageError::NodeNotInitialized => "NodeNotInitialized",
+            StorageError::NodeNotStarted => "NodeNotStarted",
+            StorageError::NodeRunning => "NodeRunning",
+            StorageError::Upload(_) => "Upload",
+            StorageError::Download(_) => "Download",
+            StorageError::FileNotFound(_) => "FileNotFound",
+            StorageError::InvalidCid(_) => "InvalidCid",
+            StorageError::Io(_) => "Io",
+            StorageError::Configuration(_) => "Configuration",
+            StorageError::Decryption(_) => "Decryption",
+            StorageError::InvalidShare(_) => "InvalidShare",
+            StorageError::PassphraseRequired => "PassphraseRequired",
+            StorageError::WrongPassphrase => "WrongPassphrase",
+            StorageError::InvalidNameRecord(_) => "InvalidNameRecord",
+            StorageError::PortInUse { .. } => "PortInUse",
+            StorageError::RepoLocked(_) => "RepoLocked",
+            StorageError::StaleRepoLock(_) => "StaleRepoLock",
+            StorageError::TransfersPaused => "TransfersPaused",
+            StorageError::ShuttingDown => "ShuttingDown",
         }
     }
 }
 
 impl std::error::Error for StorageError {}
 
+/// Returns how often each error kind reached the frontend
+pub fn error_counts() -> BTreeMap<&'static str, u64> {
+    ERROR_COUNTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
 // Convert StorageError to String for Tauri commands
 pub fn map_storage_error(err: StorageError) -> String {
+    *ERROR_COUNTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(err.kind())
+        .or_default() += 1;
     format!("{}", err)
 }
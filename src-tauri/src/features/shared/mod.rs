@@ -1,7 +1,9 @@
 pub mod error;
 pub mod progress;
+pub mod store;
 pub mod types;
 
 pub use error::*;
 pub use progress::*;
+pub use store::*;
 pub use types::*;
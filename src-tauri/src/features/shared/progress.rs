@@ -16,6 +16,10 @@ pub struct ProgressMessage {
     pub progress: f64,
     pub bytes_processed: usize,
     pub total_bytes: Option<usize>,
+    /// Size before compression when the transferred bytes are compressed
+    pub original_bytes: Option<usize>,
+    /// Content the operation produced or fetched, set once it completed
+    pub cid: Option<String>,
     pub stage: OperationStage,
     pub message: Option<String>,
 }
@@ -27,6 +31,8 @@ impl ProgressMessage {
             progress: 0.0,
             bytes_processed: 0,
             total_bytes: None,
+            original_bytes: None,
+            cid: None,
             stage: OperationStage::Initializing,
             message: None,
         }
@@ -41,6 +47,16 @@ impl ProgressMessage {
         self
     }
 
+    pub fn with_original_bytes(mut self, original_bytes: Option<usize>) -> Self {
+        self.original_bytes = original_bytes;
+        self
+    }
+
+    pub fn with_cid(mut self, cid: String) -> Self {
+        self.cid = Some(cid);
+        self
+    }
+
     pub fn with_stage(mut self, stage: OperationStage) -> Self {
         self.stage = stage;
         self
@@ -51,3 +67,11 @@ impl ProgressMessage {
         self
     }
 }
+
+/// Final progress message of a finished operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    /// Seconds since the Unix epoch
+    pub finished_at: u64,
+    pub progress: ProgressMessage,
+}
@@ -0,0 +1,69 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, warn};
+
+use crate::features::shared::StorageError;
+
+/// Reads a JSON file, falling back to `T::default()` when it does not exist yet.
+///
+/// A file that exists but cannot be parsed is moved aside to `<name>.corrupt-<unix time>`
+/// first, so the next save starts fresh without destroying what the user had.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return T::default(),
+        Err(e) => {
+            warn!("Failed to read {}: {}", path.display(), e);
+            return T::default();
+        }
+    };
+
+    match serde_json::from_str(&contents) {
+        Ok(value) => value,
+        Err(e) => {
+            let backup = backup_path(path);
+            match std::fs::rename(path, &backup) {
+                Ok(()) => warn!(
+                    "Failed to parse {}: {}, kept a copy at {}",
+                    path.display(),
+                    e,
+                    backup.display()
+                ),
+                Err(rename_error) => error!(
+                    "Failed to parse {}: {}, and could not back it up: {}",
+                    path.display(),
+                    e,
+                    rename_error
+                ),
+            }
+            T::default()
+        }
+    }
+}
+
+/// Writes a JSON file through a temporary file so a crash never leaves it half written
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), StorageError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| StorageError::Io(e.to_string()))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, contents).map_err(|e| StorageError::Io(e.to_string()))?;
+    std::fs::rename(&temp_path, path).map_err(|e| StorageError::Io(e.to_string()))
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.corrupt-{}", name, seconds))
+}
@@ -1,6 +1,8 @@
 use codex_bindings::DebugInfo;
 use serde::{Deserialize, Serialize};
 
+use crate::features::settings::RepoBackend;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum StorageConnectionStatus {
     Disconnected,
@@ -28,15 +30,317 @@ pub struct NodeInfo {
 pub struct UploadResultResponse {
     pub cid: String,
     pub size: usize,
+    /// Bytes actually stored on the network, after compression and encryption
+    pub stored_size: usize,
     pub duration_ms: u64,
     pub verified: bool,
+    pub share: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct UploadRequestOptions {
+    pub encrypt: bool,
+    pub passphrase: Option<String>,
+    pub compress: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DownloadResultResponse {
     pub cid: String,
     pub size: usize,
+    /// Bytes actually fetched from the network, before decryption and decompression
+    pub stored_size: usize,
     pub duration_ms: u64,
     pub verified: bool,
     pub filepath: Option<String>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CidInfo {
+    pub cid: String,
+    pub filename: Option<String>,
+    pub mimetype: Option<String>,
+    pub dataset_size: usize,
+    pub block_size: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub url: Option<String>,
+}
+
+impl GatewayStatus {
+    pub fn running(port: u16) -> Self {
+        Self {
+            running: true,
+            port: Some(port),
+            url: Some(format!("http://127.0.0.1:{}", port)),
+        }
+    }
+
+    pub fn stopped() -> Self {
+        Self {
+            running: false,
+            port: None,
+            url: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetricsEndpointStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub url: Option<String>,
+}
+
+impl MetricsEndpointStatus {
+    pub fn running(port: u16) -> Self {
+        Self {
+            running: true,
+            port: Some(port),
+            url: Some(format!("http://127.0.0.1:{}/metrics", port)),
+        }
+    }
+
+    pub fn stopped() -> Self {
+        Self {
+            running: false,
+            port: None,
+            url: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchFolderStatus {
+    pub path: String,
+    pub active: bool,
+    pub uploaded_files: usize,
+    pub pending_files: usize,
+    pub last_upload: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MirrorSummary {
+    pub root_cid: String,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: usize,
+    pub removed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedDirectory {
+    pub cid: String,
+    pub files: usize,
+    pub total_size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NameResolution {
+    pub address: String,
+    pub cid: String,
+    pub sequence: u64,
+    pub record_cid: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IdentityArchiveInfo {
+    pub profile: String,
+    pub peer_id: Option<String>,
+    pub created_at: u64,
+    pub includes_metadata: bool,
+    pub files: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DatasetIssue {
+    pub cid: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepositoryReport {
+    pub datasets: usize,
+    pub verified: usize,
+    /// Datasets that cannot be read completely from the local store. The bindings
+    /// report a missing block and one that fails its hash alike, so the error text
+    /// is the only hint which of the two it is.
+    pub unreadable: Vec<DatasetIssue>,
+    pub total_blocks: usize,
+    pub referenced_blocks: usize,
+    /// Blocks no manifest accounts for, derived from the block counts. The bindings
+    /// have no way to collect them, so this is only reported.
+    pub orphaned_blocks: usize,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DatasetRemovalResult {
+    pub removed: Vec<String>,
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MigrationResult {
+    pub backend: RepoBackend,
+    pub datasets: usize,
+    pub duration_ms: u64,
+}
+
+/// Cumulative transfer totals since the app started
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TransferCounters {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub uploads_completed: u64,
+    pub downloads_completed: u64,
+    pub failed: u64,
+    pub upload_duration_ms: u64,
+    pub download_duration_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkSample {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub status: StorageConnectionStatus,
+    pub connected_peers: usize,
+    pub dht_table_size: usize,
+    /// Blocks the node sent to and received from peers. The bindings expose no
+    /// block exchange counters yet, so both stay `None` until they do.
+    pub blocks_sent: Option<u64>,
+    pub blocks_received: Option<u64>,
+    /// Bytes of the uploads and downloads started in this app. Blocks the node
+    /// serves to other peers while seeding are not included.
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub bytes_in_per_sec: f64,
+    pub bytes_out_per_sec: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkStats {
+    pub interval_secs: u64,
+    pub samples: Vec<NetworkSample>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum PeerDirection {
+    Inbound,
+    Outbound,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConnectedPeer {
+    pub peer_id: String,
+    pub addresses: Vec<String>,
+    pub direction: PeerDirection,
+    /// Seconds since storeman first listed the peer as connected. The bindings do
+    /// not expose when a connection opened, so it may be older than this.
+    pub observed_secs: u64,
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BannedPeer {
+    pub peer_id: String,
+    pub reason: Option<String>,
+    /// Seconds since the Unix epoch
+    pub banned_at: u64,
+}
+
+/// Decoded content of a signed peer record
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SprInfo {
+    pub spr: String,
+    pub key_type: String,
+    pub public_key: String,
+    pub peer_id: String,
+    pub sequence: u64,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BootstrapPreset {
+    pub name: String,
+    pub description: String,
+    /// Whether the bindings' built-in bootstrap nodes are used while no nodes are set
+    pub uses_builtin: bool,
+    pub nodes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BootstrapNodeStatus {
+    pub spr: String,
+    pub addresses: Vec<String>,
+    pub reachable: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BootstrapReport {
+    /// Seconds since the Unix epoch
+    pub checked_at: u64,
+    pub preset: String,
+    pub nodes: Vec<BootstrapNodeStatus>,
+    pub dht_table_size: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LanPeer {
+    pub peer_id: String,
+    pub addresses: Vec<String>,
+    /// Seconds since the Unix epoch
+    pub last_seen: u64,
+    pub connected: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReachabilityReport {
+    /// Addresses the node is listening on
+    pub listen_addrs: Vec<String>,
+    /// Addresses the node tells other peers to dial
+    pub announce_addrs: Vec<String>,
+    /// Whether any announced address is publicly routable
+    pub announces_public_address: bool,
+    /// Whether every announced TCP port is one the node listens on
+    pub announce_ports_match: bool,
+    /// Whether a second local node could open a connection to this one
+    pub accepts_inbound: bool,
+    pub dial_error: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Arguments of a launch that was handed over to the running instance
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SecondInstanceArgs {
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepoLockStatus {
+    pub data_dir: String,
+    pub lock_files: Vec<String>,
+    /// Lock files some process currently holds, these are never cleared
+    pub held_lock_files: Vec<String>,
+    /// Opening the node fails on a lock that no process holds
+    pub stale: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExitRequest {
+    pub active_transfers: usize,
+    /// Seconds transfers get to finish before they are cancelled
+    pub timeout_secs: u64,
+}
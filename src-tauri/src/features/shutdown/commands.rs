@@ -0,0 +1,9 @@
+use crate::features::shutdown::exit_with_handle;
+use tauri::AppHandle;
+
+/// Called by the frontend once the user confirmed exiting with transfers running
+#[tauri::command]
+pub async fn confirm_exit(wait_for_transfers: bool, app_handle: AppHandle) -> Result<(), String> {
+    exit_with_handle(wait_for_transfers, app_handle).await;
+    Ok(())
+}
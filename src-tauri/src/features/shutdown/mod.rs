@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod shutdown;
+
+pub use commands::*;
+pub use shutdown::*;
@@ -0,0 +1,175 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, Window, WindowEvent};
+use tracing::{error, info, warn};
+
+use crate::features::connection::{set_transfers_paused, STORAGE_MANAGER};
+use crate::features::instance::release_instance_lock;
+use crate::features::shared::{ExitRequest, StorageError};
+
+pub const EXIT_REQUESTED_EVENT: &str = "exit-requested";
+
+/// Upper bound for draining transfers and stopping the node before exiting anyway
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+// Set once the node is down, the next exit request is let through
+static EXIT_READY: AtomicBool = AtomicBool::new(false);
+
+/// Whether the app is exiting, new transfers are refused from then on
+pub fn shutdown_started() -> bool {
+    SHUTDOWN_STARTED.load(Ordering::SeqCst)
+}
+
+async fn active_operations() -> usize {
+    match STORAGE_MANAGER.get() {
+        Some(manager) => manager.active_operation_count().await,
+        None => 0,
+    }
+}
+
+/// Waits for running transfers if asked to, then stops and destroys the node
+async fn stop_node_gracefully(wait_for_transfers: bool) -> Result<(), StorageError> {
+    let Some(manager) = STORAGE_MANAGER.get() else {
+        return Ok(());
+    };
+
+    if wait_for_transfers {
+        loop {
+            let remaining = manager.active_operation_count().await;
+            if remaining == 0 {
+                break;
+            }
+            info!(
+                "Waiting for {} transfer(s) to finish before exit",
+                remaining
+            );
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    }
+
+    // Transfers still running fail once the node stops
+    manager.release_node().await
+}
+
+async fn shutdown(wait_for_transfers: bool) {
+    let started = Instant::now();
+    // Watch folders, mirrors and the gateway must not start anything new meanwhile
+    set_transfers_paused(true);
+    info!(
+        "Shutting down, {} transfer(s) running, waiting for them: {}",
+        active_operations().await,
+        wait_for_transfers
+    );
+
+    match tokio::time::timeout(SHUTDOWN_TIMEOUT, stop_node_gracefully(wait_for_transfers)).await {
+        Ok(Ok(())) => info!("Node stopped in {} ms", started.elapsed().as_millis()),
+        Ok(Err(e)) => error!("Failed to stop node cleanly: {}", e),
+        Err(_) => warn!(
+            "Node did not stop within {}s, exiting anyway",
+            SHUTDOWN_TIMEOUT.as_secs()
+        ),
+    }
+
+    release_instance_lock();
+}
+
+/// Stops the node and exits the app.
+///
+/// With `wait_for_transfers` running uploads and downloads are given until the
+/// shutdown timeout to finish, otherwise they are cancelled by stopping the node.
+pub async fn exit_with_handle(wait_for_transfers: bool, app_handle: AppHandle) {
+    if SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    shutdown(wait_for_transfers).await;
+    EXIT_READY.store(true, Ordering::SeqCst);
+    app_handle.exit(0);
+}
+
+/// Whether the main window is on screen to show the exit confirmation
+fn can_confirm(app_handle: &AppHandle) -> bool {
+    app_handle
+        .get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Asks the frontend to confirm when transfers are running, returns whether
+/// the close or exit may go ahead right away
+fn confirm_if_busy(app_handle: &AppHandle) -> bool {
+    let running = tauri::async_runtime::block_on(active_operations());
+    if running == 0 {
+        return true;
+    }
+
+    // Hidden to the tray or without a window nobody can confirm, so transfers
+    // get until the timeout
+    if !can_confirm(app_handle) {
+        info!(
+            "Exit requested with {} transfer(s) running, waiting for them",
+            running
+        );
+        tauri::async_runtime::spawn(exit_with_handle(true, app_handle.clone()));
+        return false;
+    }
+
+    info!("Exit requested with {} transfer(s) running", running);
+    let _ = app_handle.emit(
+        EXIT_REQUESTED_EVENT,
+        ExitRequest {
+            active_transfers: running,
+            timeout_secs: SHUTDOWN_TIMEOUT.as_secs(),
+        },
+    );
+    false
+}
+
+/// Exits once the node is stopped, or asks the frontend first when transfers are running
+pub fn request_exit(app_handle: &AppHandle) {
+    if SHUTDOWN_STARTED.load(Ordering::SeqCst) {
+        return;
+    }
+    if confirm_if_busy(app_handle) {
+        tauri::async_runtime::spawn(exit_with_handle(false, app_handle.clone()));
+    }
+}
+
+/// Keeps the main window open while transfers are running so the user can
+/// confirm the exit. On desktop the window may go to the tray instead.
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if SHUTDOWN_STARTED.load(Ordering::SeqCst) {
+            return;
+        }
+        #[cfg(desktop)]
+        if crate::features::tray::hide_to_tray(window) {
+            api.prevent_close();
+            return;
+        }
+        if !confirm_if_busy(window.app_handle()) {
+            api.prevent_close();
+        }
+    }
+}
+
+/// Holds back process exit until the node has been stopped
+pub fn handle_run_event(app_handle: &AppHandle, event: &RunEvent) {
+    match event {
+        RunEvent::ExitRequested { api, .. } => {
+            if EXIT_READY.load(Ordering::SeqCst) {
+                return;
+            }
+            api.prevent_exit();
+            request_exit(app_handle);
+        }
+        RunEvent::Exit => {
+            // Exits that could not be prevented still get a bounded cleanup
+            if !SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+                tauri::async_runtime::block_on(shutdown(false));
+            }
+        }
+        _ => {}
+    }
+}
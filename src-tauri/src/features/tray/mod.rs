@@ -0,0 +1,3 @@
+pub mod tray;
+
+pub use tray::*;
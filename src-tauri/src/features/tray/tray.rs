@@ -0,0 +1,258 @@
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Window, Wry};
+use tauri_plugin_autostart::ManagerExt;
+use tracing::{error, info};
+
+use crate::features::connection::{
+    set_transfers_paused, start_node, stop_node, transfers_paused, STORAGE_MANAGER,
+};
+use crate::features::settings::{load_settings, BackgroundSettings};
+use crate::features::shared::{OperationStage, StorageConnectionStatus, StorageError};
+use crate::features::shutdown::request_exit;
+
+pub const OPEN_CID_EVENT: &str = "open-cid";
+
+/// Passed by the login item so a launch at login can start hidden
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+const TRAY_ID: &str = "main";
+const RECENT_CID_LIMIT: usize = 5;
+const RECENT_CID_PREFIX: &str = "recent:";
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// What the tray menu shows, the menu is only rebuilt when this changes
+#[derive(Debug, Clone, PartialEq)]
+struct TrayState {
+    node_running: bool,
+    paused: bool,
+    recent_cids: Vec<String>,
+}
+
+async fn tray_state() -> TrayState {
+    let (node_running, recent_cids) = match STORAGE_MANAGER.get() {
+        Some(manager) => {
+            let mut recent_cids: Vec<String> = Vec::new();
+            for record in manager.operation_history().await.iter().rev() {
+                let progress = &record.progress;
+                if let (OperationStage::Completed, Some(cid)) = (&progress.stage, &progress.cid) {
+                    if !recent_cids.contains(cid) {
+                        recent_cids.push(cid.clone());
+                    }
+                }
+                if recent_cids.len() == RECENT_CID_LIMIT {
+                    break;
+                }
+            }
+            (
+                manager.get_status().await == StorageConnectionStatus::Connected,
+                recent_cids,
+            )
+        }
+        None => (false, Vec::new()),
+    };
+
+    TrayState {
+        node_running,
+        paused: transfers_paused(),
+        recent_cids,
+    }
+}
+
+fn build_menu(app_handle: &AppHandle, state: &TrayState) -> tauri::Result<Menu<Wry>> {
+    let open = MenuItem::with_id(app_handle, "open", "Open storeman", true, None::<&str>)?;
+    let node_label = if state.node_running {
+        "Stop node"
+    } else {
+        "Start node"
+    };
+    let toggle_node = MenuItem::with_id(app_handle, "toggle_node", node_label, true, None::<&str>)?;
+    let pause = CheckMenuItem::with_id(
+        app_handle,
+        "pause_transfers",
+        "Pause background transfers",
+        true,
+        state.paused,
+        None::<&str>,
+    )?;
+
+    let cid_items = state
+        .recent_cids
+        .iter()
+        .map(|cid| {
+            MenuItem::with_id(
+                app_handle,
+                format!("{}{}", RECENT_CID_PREFIX, cid),
+                cid,
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let cid_refs: Vec<&dyn IsMenuItem<Wry>> = cid_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let recent = Submenu::with_items(app_handle, "Recent CIDs", !cid_refs.is_empty(), &cid_refs)?;
+
+    let quit = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(
+        app_handle,
+        &[
+            &open,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &toggle_node,
+            &pause,
+            &recent,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &quit,
+        ],
+    )
+}
+
+pub fn show_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+async fn toggle_node(app_handle: AppHandle) {
+    let running = tray_state().await.node_running;
+    let result = if running {
+        stop_node(app_handle.clone()).await
+    } else {
+        start_node(app_handle.clone()).await
+    };
+    if let Err(e) = result {
+        error!("Tray failed to toggle node: {}", e);
+    }
+    refresh_tray_menu(&app_handle).await;
+}
+
+fn handle_menu_event(app_handle: &AppHandle, id: &str) {
+    match id {
+        "open" => show_main_window(app_handle),
+        "toggle_node" => {
+            tauri::async_runtime::spawn(toggle_node(app_handle.clone()));
+        }
+        "pause_transfers" => {
+            set_transfers_paused(!transfers_paused());
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move { refresh_tray_menu(&app_handle).await });
+        }
+        "quit" => request_exit(app_handle),
+        _ => {
+            if let Some(cid) = id.strip_prefix(RECENT_CID_PREFIX) {
+                show_main_window(app_handle);
+                let _ = app_handle.emit(OPEN_CID_EVENT, cid.to_string());
+            }
+        }
+    }
+}
+
+/// Rebuilds the tray menu from the current node and transfer state
+pub async fn refresh_tray_menu(app_handle: &AppHandle) {
+    let state = tray_state().await;
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app_handle, &state) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => error!("Failed to build tray menu: {}", e),
+    }
+}
+
+/// Adds the tray icon and keeps its menu in sync with the node
+pub fn create_tray(app_handle: &AppHandle) -> Result<(), StorageError> {
+    let state = tauri::async_runtime::block_on(tray_state());
+    let menu =
+        build_menu(app_handle, &state).map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("storeman")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app_handle, event| handle_menu_event(app_handle, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app_handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder
+        .build(app_handle)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut shown = state;
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current = tray_state().await;
+            if current != shown {
+                refresh_tray_menu(&app_handle).await;
+                shown = current;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Hides the window instead of closing it when the node should keep running in
+/// the tray, returns whether the close was taken over
+pub fn hide_to_tray(window: &Window) -> bool {
+    if !load_settings(window.app_handle()).background.close_to_tray {
+        return false;
+    }
+    // Without a tray icon there would be no way to bring the window back
+    if window.app_handle().tray_by_id(TRAY_ID).is_none() {
+        return false;
+    }
+    if let Err(e) = window.hide() {
+        error!("Failed to hide window to tray: {}", e);
+        return false;
+    }
+    info!("Window hidden to tray, node keeps running");
+    true
+}
+
+/// Hides the main window right away when launched at login with start minimized
+pub fn apply_start_minimized(app_handle: &AppHandle) {
+    let launched_at_login = std::env::args().any(|arg| arg == MINIMIZED_ARG);
+    if launched_at_login && load_settings(app_handle).background.start_minimized {
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Registers or removes the login item to match the settings
+pub fn apply_background_settings(
+    settings: &BackgroundSettings,
+    app_handle: &AppHandle,
+) -> Result<(), StorageError> {
+    let autolaunch = app_handle.autolaunch();
+    let enabled = autolaunch
+        .is_enabled()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    let result = match (settings.launch_at_login, enabled) {
+        (true, false) => autolaunch.enable(),
+        (false, true) => autolaunch.disable(),
+        _ => Ok(()),
+    };
+    result.map_err(|e| StorageError::Configuration(e.to_string()))
+}
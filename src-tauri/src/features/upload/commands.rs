@@ -1,13 +1,14 @@
-use crate::features::shared::map_storage_error;
-use crate::features::upload::upload_file_with_progress;
+use crate::features::shared::{map_storage_error, UploadRequestOptions};
+use crate::features::upload::upload_file_with_options;
 use tauri::AppHandle;
 
 #[tauri::command]
 pub async fn upload_file_to_storage(
     file_path: String,
+    options: Option<UploadRequestOptions>,
     app_handle: AppHandle,
 ) -> Result<crate::features::shared::UploadResultResponse, String> {
-    upload_file_with_progress(file_path.into(), app_handle)
+    upload_file_with_options(file_path.into(), options.unwrap_or_default(), app_handle)
         .await
         .map_err(map_storage_error)
 }
@@ -1,23 +1,138 @@
 use codex_bindings::{upload_file, UploadOptions};
 use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::Manager;
 use uuid::Uuid;
 
-use crate::features::connection::get_storage_manager_with_handle;
+use crate::features::compression::{compress_file, COMPRESSED_MIMETYPE};
+use crate::features::connection::{get_storage_manager_with_handle, StorageManager};
+use crate::features::encryption::{build_share, encrypt_file, EncryptionKey, EncryptionSecret};
 use crate::features::shared::{
-    OperationStage, ProgressMessage, StorageError, UploadResultResponse,
+    OperationStage, ProgressMessage, StorageError, UploadRequestOptions, UploadResultResponse,
 };
 
+/// Uploads a file after applying the optional client-side transforms.
+///
+/// Compression runs before encryption, ciphertext does not compress.
+pub async fn upload_file_with_options(
+    file_path: PathBuf,
+    options: UploadRequestOptions,
+    app_handle: tauri::AppHandle,
+) -> Result<UploadResultResponse, StorageError> {
+    // A passphrase takes precedence over a random key, the receiver already knows it
+    let secret = match (options.passphrase, options.encrypt) {
+        (Some(passphrase), _) if !passphrase.is_empty() => {
+            Some(EncryptionSecret::Passphrase(passphrase))
+        }
+        (_, true) => Some(EncryptionSecret::Key(EncryptionKey::generate())),
+        _ => None,
+    };
+
+    if secret.is_none() && !options.compress {
+        return upload_file_with_progress(file_path, app_handle).await;
+    }
+
+    if !file_path.exists() {
+        return Err(StorageError::FileNotFound(
+            file_path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let original_size = std::fs::metadata(&file_path)
+        .map_err(|e| StorageError::Io(e.to_string()))?
+        .len() as usize;
+
+    let temp_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| StorageError::Configuration(e.to_string()))?
+        .join("uploads");
+    std::fs::create_dir_all(&temp_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+    let compressed_path = temp_dir.join(format!("{}.zst", Uuid::new_v4()));
+    let encrypted_path = temp_dir.join(format!("{}.enc", Uuid::new_v4()));
+
+    let compress = options.compress;
+    let prepare_compressed = compressed_path.clone();
+    let prepare_encrypted = encrypted_path.clone();
+    let prepared = tokio::task::spawn_blocking(move || {
+        let mut current = file_path;
+
+        if compress {
+            compress_file(&current, &prepare_compressed)?;
+            current = prepare_compressed;
+        }
+
+        if let Some(secret) = &secret {
+            encrypt_file(&current, &prepare_encrypted, secret)?;
+            current = prepare_encrypted;
+        }
+
+        Ok::<_, StorageError>((current, secret))
+    })
+    .await
+    .map_err(|e| StorageError::Upload(e.to_string()));
+
+    let result = match prepared {
+        Ok(Ok((upload_path, secret))) => {
+            // The manifest marks compression so downloads never guess from the content
+            let mimetype = compress.then(|| COMPRESSED_MIMETYPE.to_string());
+            upload_file_with_sizes(upload_path, Some(original_size), mimetype, app_handle)
+                .await
+                .map(|response| (response, secret))
+        }
+        Ok(Err(e)) | Err(e) => Err(e),
+    };
+
+    let _ = std::fs::remove_file(&compressed_path);
+    let _ = std::fs::remove_file(&encrypted_path);
+
+    let (mut response, secret) = result?;
+    // Passphrase uploads are shared by CID alone, the passphrase travels separately
+    if let Some(EncryptionSecret::Key(key)) = &secret {
+        response.share = Some(build_share(&response.cid, key));
+    }
+    Ok(response)
+}
+
 pub async fn upload_file_with_progress(
     file_path: PathBuf,
     app_handle: tauri::AppHandle,
+) -> Result<UploadResultResponse, StorageError> {
+    upload_file_with_sizes(file_path, None, None, app_handle).await
+}
+
+/// Uploads `file_path` as is, `original_size` is the size of the file it was derived
+/// from when the upload was compressed or encrypted beforehand
+async fn upload_file_with_sizes(
+    file_path: PathBuf,
+    original_size: Option<usize>,
+    mimetype: Option<String>,
+    app_handle: tauri::AppHandle,
 ) -> Result<UploadResultResponse, StorageError> {
     let manager = get_storage_manager_with_handle(Some(app_handle)).await?;
 
     let operation_id = Uuid::new_v4().to_string();
 
-    // Register progress sender
-    let _rx = manager.register_progress_sender(operation_id.clone()).await;
+    // The sender is unregistered whichever way this returns
+    let _progress = manager.track_progress(operation_id.clone()).await?;
 
+    run_upload(
+        manager.clone(),
+        operation_id,
+        file_path,
+        original_size,
+        mimetype,
+    )
+    .await
+}
+
+async fn run_upload(
+    manager: Arc<StorageManager>,
+    operation_id: String,
+    file_path: PathBuf,
+    original_size: Option<usize>,
+    mimetype: Option<String>,
+) -> Result<UploadResultResponse, StorageError> {
     // Send initial progress
     let initial_progress =
         ProgressMessage::new(operation_id.clone()).with_stage(OperationStage::Initializing);
@@ -45,6 +160,7 @@ pub async fn upload_file_with_progress(
     let size_progress = ProgressMessage::new(operation_id.clone())
         .with_stage(OperationStage::Uploading)
         .with_bytes(0, Some(file_size))
+        .with_original_bytes(original_size)
         .with_message(format!("Starting upload of {} bytes", file_size));
     manager.send_progress(&operation_id, size_progress).await;
 
@@ -60,12 +176,17 @@ pub async fn upload_file_with_progress(
                 let progress_msg = ProgressMessage::new(operation_id_for_callback.clone())
                     .with_stage(OperationStage::Uploading)
                     .with_bytes(progress.bytes_uploaded, progress.total_bytes)
+                    .with_original_bytes(original_size)
                     .with_message(format!("Uploaded {} bytes", progress.bytes_uploaded));
                 manager
                     .send_progress(&operation_id_for_callback, progress_msg)
                     .await;
             });
         });
+    let upload_options = match mimetype {
+        Some(mimetype) => upload_options.mimetype(mimetype),
+        None => upload_options,
+    };
 
     // Perform the upload
     let result = upload_file(&node, upload_options)
@@ -76,18 +197,19 @@ pub async fn upload_file_with_progress(
     let completion_progress = ProgressMessage::new(operation_id.clone())
         .with_stage(OperationStage::Completed)
         .with_bytes(file_size, Some(file_size))
+        .with_original_bytes(original_size)
+        .with_cid(result.cid.clone())
         .with_message("Upload completed successfully".to_string());
     manager
         .send_progress(&operation_id, completion_progress)
         .await;
 
-    // Clean up progress sender
-    manager.unregister_progress_sender(&operation_id).await;
-
     Ok(UploadResultResponse {
         cid: result.cid,
-        size: file_size,
+        size: original_size.unwrap_or(file_size),
+        stored_size: file_size,
         duration_ms: 0, // TODO: Track actual duration
         verified: true,
+        share: None,
     })
 }
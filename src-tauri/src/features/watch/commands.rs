@@ -0,0 +1,27 @@
+use crate::features::shared::{map_storage_error, WatchFolderStatus};
+use crate::features::watch::{
+    add_watch_folder_with_handle, list_watch_folder_statuses, remove_watch_folder_with_handle,
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn add_watch_folder(
+    path: String,
+    app_handle: AppHandle,
+) -> Result<WatchFolderStatus, String> {
+    add_watch_folder_with_handle(path.into(), app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn remove_watch_folder(path: String, app_handle: AppHandle) -> Result<(), String> {
+    remove_watch_folder_with_handle(path.into(), app_handle)
+        .await
+        .map_err(map_storage_error)
+}
+
+#[tauri::command]
+pub async fn list_watch_folders() -> Result<Vec<WatchFolderStatus>, String> {
+    Ok(list_watch_folder_statuses().await)
+}
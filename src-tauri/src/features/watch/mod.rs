@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod watch;
+
+pub use commands::*;
+pub use watch::*;
@@ -0,0 +1,434 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use crate::features::connection::transfers_paused;
+use crate::features::shared::{load_json, save_json, StorageError, WatchFolderStatus};
+use crate::features::upload::upload_file_with_progress;
+
+const INDEX_FILE: &str = "watch_index.json";
+
+// A file must stay untouched this long before it is considered fully written
+const DEBOUNCE: Duration = Duration::from_secs(2);
+const TICK: Duration = Duration::from_secs(1);
+
+/// Upload record for a single file inside a watched folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedFile {
+    pub cid: String,
+    pub size: u64,
+    pub modified: u64,
+    pub uploaded_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchedFolder {
+    pub path: PathBuf,
+    /// Keyed by path relative to the watched folder
+    pub files: HashMap<String, WatchedFile>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchIndex {
+    pub folders: Vec<WatchedFolder>,
+}
+
+#[derive(Default)]
+struct FolderRuntime {
+    pending: usize,
+    last_error: Option<String>,
+}
+
+struct RunningWatch {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+    runtime: Arc<Mutex<FolderRuntime>>,
+}
+
+struct PendingFile {
+    last_event: Instant,
+    last_size: Option<u64>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    index: WatchIndex,
+    running: HashMap<PathBuf, RunningWatch>,
+}
+
+// Global watch state, shared by every folder worker
+static WATCH_STATE: Lazy<Mutex<WatchState>> = Lazy::new(|| Mutex::new(WatchState::default()));
+
+fn index_path(app_handle: &AppHandle) -> Result<PathBuf, StorageError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(INDEX_FILE))
+        .map_err(|e| StorageError::Configuration(e.to_string()))
+}
+
+fn load_index(app_handle: &AppHandle) -> WatchIndex {
+    match index_path(app_handle) {
+        Ok(path) => load_json(&path),
+        Err(_) => WatchIndex::default(),
+    }
+}
+
+fn save_index(app_handle: &AppHandle, index: &WatchIndex) -> Result<(), StorageError> {
+    save_json(&index_path(app_handle)?, index)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Skips hidden files and everything inside hidden folders such as `.git` below
+/// `root`, which also covers the temporary files storeman writes itself
+fn is_watchable(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    relative.components().next().is_some()
+        && relative
+            .components()
+            .all(|component| !component.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+/// Lists the watchable files below `root`. Symlinks are never followed, so the walk
+/// stays inside `root` and cannot loop.
+pub fn collect_files(root: &Path, files: &mut Vec<PathBuf>) {
+    collect_dir(root, root, files);
+}
+
+fn collect_dir(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !is_watchable(root, &path) {
+            continue;
+        }
+        // The entry type describes a symlink itself, not its target
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => collect_dir(root, &path, files),
+            Ok(file_type) if file_type.is_file() => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// Starts watching a folder and records it in the persistent index
+pub async fn add_watch_folder_with_handle(
+    path: PathBuf,
+    app_handle: AppHandle,
+) -> Result<WatchFolderStatus, StorageError> {
+    if !path.is_dir() {
+        return Err(StorageError::FileNotFound(
+            path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let path = path
+        .canonicalize()
+        .map_err(|e| StorageError::Io(e.to_string()))?;
+
+    {
+        let mut state = WATCH_STATE.lock().await;
+        if !state.index.folders.iter().any(|folder| folder.path == path) {
+            state.index.folders.push(WatchedFolder {
+                path: path.clone(),
+                files: HashMap::new(),
+            });
+            save_index(&app_handle, &state.index)?;
+        }
+    }
+
+    start_folder_watch(path.clone(), app_handle).await?;
+    folder_status(&path).await
+}
+
+/// Stops watching a folder and forgets its upload records
+pub async fn remove_watch_folder_with_handle(
+    path: PathBuf,
+    app_handle: AppHandle,
+) -> Result<(), StorageError> {
+    let path = path.canonicalize().unwrap_or(path);
+
+    let mut state = WATCH_STATE.lock().await;
+    if let Some(running) = state.running.remove(&path) {
+        running.task.abort();
+    }
+    state.index.folders.retain(|folder| folder.path != path);
+    save_index(&app_handle, &state.index)
+}
+
+pub async fn list_watch_folder_statuses() -> Vec<WatchFolderStatus> {
+    let paths: Vec<PathBuf> = {
+        let state = WATCH_STATE.lock().await;
+        state
+            .index
+            .folders
+            .iter()
+            .map(|folder| folder.path.clone())
+            .collect()
+    };
+
+    let mut statuses = Vec::with_capacity(paths.len());
+    for path in paths {
+        if let Ok(status) = folder_status(&path).await {
+            statuses.push(status);
+        }
+    }
+    statuses
+}
+
+/// Forgets which files were uploaded, e.g. after the repository was wiped.
+///
+/// Every file in a watched folder is uploaded again on the next scan.
+pub async fn clear_watch_uploads(app_handle: &AppHandle) -> Result<(), StorageError> {
+    let mut state = WATCH_STATE.lock().await;
+    for folder in state.index.folders.iter_mut() {
+        folder.files.clear();
+    }
+    save_index(app_handle, &state.index)
+}
+
+/// Reloads the persisted index and resumes watching every folder in it
+pub async fn restore_watch_folders(app_handle: AppHandle) {
+    let paths: Vec<PathBuf> = {
+        let mut state = WATCH_STATE.lock().await;
+        state.index = load_index(&app_handle);
+        state
+            .index
+            .folders
+            .iter()
+            .map(|folder| folder.path.clone())
+            .collect()
+    };
+
+    for path in paths {
+        if let Err(e) = start_folder_watch(path.clone(), app_handle.clone()).await {
+            error!("Failed to resume watching {}: {}", path.display(), e);
+        }
+    }
+}
+
+async fn folder_status(path: &Path) -> Result<WatchFolderStatus, StorageError> {
+    let state = WATCH_STATE.lock().await;
+    let folder = state
+        .index
+        .folders
+        .iter()
+        .find(|folder| folder.path == path)
+        .ok_or_else(|| StorageError::FileNotFound(path.to_string_lossy().to_string()))?;
+
+    let (active, pending_files, last_error) = match state.running.get(path) {
+        Some(running) => {
+            let runtime = running.runtime.lock().await;
+            (
+                !running.task.is_finished(),
+                runtime.pending,
+                runtime.last_error.clone(),
+            )
+        }
+        None => (false, 0, None),
+    };
+
+    Ok(WatchFolderStatus {
+        path: path.to_string_lossy().to_string(),
+        active,
+        uploaded_files: folder.files.len(),
+        pending_files,
+        last_upload: folder.files.values().map(|file| file.uploaded_at).max(),
+        last_error,
+    })
+}
+
+async fn start_folder_watch(path: PathBuf, app_handle: AppHandle) -> Result<(), StorageError> {
+    let mut state = WATCH_STATE.lock().await;
+    if state.running.contains_key(&path) {
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::unbounded_channel::<PathBuf>();
+    let event_tx = tx.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            for path in event.paths {
+                let _ = event_tx.send(path);
+            }
+        }
+    })
+    .map_err(|e| StorageError::Configuration(e.to_string()))?;
+    watcher
+        .watch(&path, RecursiveMode::Recursive)
+        .map_err(|e| StorageError::Configuration(e.to_string()))?;
+
+    // Queue everything that changed while storeman was not watching
+    let mut existing = Vec::new();
+    collect_files(&path, &mut existing);
+    for file in existing {
+        let _ = tx.send(file);
+    }
+
+    let runtime = Arc::new(Mutex::new(FolderRuntime::default()));
+    let task = tokio::spawn(run_folder_worker(
+        path.clone(),
+        rx,
+        runtime.clone(),
+        app_handle,
+    ));
+
+    state.running.insert(
+        path,
+        RunningWatch {
+            _watcher: watcher,
+            task,
+            runtime,
+        },
+    );
+
+    Ok(())
+}
+
+async fn run_folder_worker(
+    root: PathBuf,
+    mut rx: mpsc::UnboundedReceiver<PathBuf>,
+    runtime: Arc<Mutex<FolderRuntime>>,
+    app_handle: AppHandle,
+) {
+    let mut pending: HashMap<PathBuf, PendingFile> = HashMap::new();
+    let mut interval = tokio::time::interval(TICK);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let Some(path) = event else { break };
+                if is_watchable(&root, &path) {
+                    pending.insert(
+                        path,
+                        PendingFile {
+                            last_event: Instant::now(),
+                            last_size: None,
+                        },
+                    );
+                }
+            }
+            _ = interval.tick() => {
+                // Changes keep queueing up while paused and are uploaded on resume
+                if !transfers_paused() {
+                    process_pending(&root, &mut pending, &runtime, &app_handle).await;
+                }
+                runtime.lock().await.pending = pending.len();
+            }
+        }
+    }
+}
+
+async fn process_pending(
+    root: &Path,
+    pending: &mut HashMap<PathBuf, PendingFile>,
+    runtime: &Arc<Mutex<FolderRuntime>>,
+    app_handle: &AppHandle,
+) {
+    let ready: Vec<PathBuf> = pending
+        .iter()
+        .filter(|(_, file)| file.last_event.elapsed() >= DEBOUNCE)
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    for path in ready {
+        let metadata = match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            // Deleted, a directory event or a symlink
+            _ => {
+                pending.remove(&path);
+                continue;
+            }
+        };
+
+        // Wait for one more tick if the file is still growing
+        let size = metadata.len();
+        if let Some(file) = pending.get_mut(&path) {
+            if file.last_size != Some(size) {
+                file.last_size = Some(size);
+                continue;
+            }
+        }
+
+        let relative = match path.strip_prefix(root) {
+            Ok(relative) => relative.to_string_lossy().to_string(),
+            Err(_) => {
+                pending.remove(&path);
+                continue;
+            }
+        };
+        let modified = metadata.modified().map(unix_seconds).unwrap_or_default();
+
+        let unchanged = {
+            let state = WATCH_STATE.lock().await;
+            state
+                .index
+                .folders
+                .iter()
+                .find(|folder| folder.path == root)
+                .and_then(|folder| folder.files.get(&relative))
+                .map(|file| file.size == size && file.modified == modified)
+                .unwrap_or(false)
+        };
+        if unchanged {
+            pending.remove(&path);
+            continue;
+        }
+
+        match upload_file_with_progress(path.clone(), app_handle.clone()).await {
+            Ok(result) => {
+                pending.remove(&path);
+                runtime.lock().await.last_error = None;
+
+                let mut state = WATCH_STATE.lock().await;
+                if let Some(folder) = state
+                    .index
+                    .folders
+                    .iter_mut()
+                    .find(|folder| folder.path == root)
+                {
+                    folder.files.insert(
+                        relative,
+                        WatchedFile {
+                            cid: result.cid,
+                            size,
+                            modified,
+                            uploaded_at: unix_seconds(SystemTime::now()),
+                        },
+                    );
+                }
+                if let Err(e) = save_index(app_handle, &state.index) {
+                    error!("Failed to save watch index: {}", e);
+                }
+            }
+            // Keep the file queued until the node is back
+            Err(e @ (StorageError::NodeNotStarted | StorageError::NodeNotInitialized)) => {
+                runtime.lock().await.last_error = Some(e.to_string());
+                break;
+            }
+            Err(e) => {
+                pending.remove(&path);
+                runtime.lock().await.last_error = Some(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+}
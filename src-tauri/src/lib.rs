@@ -2,28 +2,60 @@ mod features;
 
 use tauri::Manager;
 use tauri_plugin_fs::FsExt;
+use tracing::{error, info};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+
+    // Must be the first plugin so a second launch exits before doing anything else
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        features::instance::handle_second_instance(app, args, cwd);
+    }));
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_autostart::init(
+        tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+        Some(vec![features::tray::MINIMIZED_ARG]),
+    ));
+
+    builder
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
+            if let Err(e) = features::logging::init_logging(app.handle()) {
+                eprintln!("Failed to initialize logging: {}", e);
+            }
+
+            if let Err(e) = features::instance::acquire_instance_lock(app.handle()) {
+                error!("Refusing to start: {}", e);
+                return Err(Box::new(e));
+            }
+
+            // The node lives on in the tray when the window is closed
+            #[cfg(desktop)]
+            {
+                features::tray::apply_start_minimized(app.handle());
+                if let Err(e) = features::tray::create_tray(app.handle()) {
+                    error!("Failed to create tray icon: {}", e);
+                }
+            }
+
             let fs = app.fs_scope();
 
             if let Ok(app_data_dir) = app.path().app_data_dir() {
                 let storage_dir = app_data_dir.join("storage_data");
                 fs.allow_directory(&storage_dir, true)
                     .expect("Failed to allow Storage data directory");
-                println!("Allowed Storage data directory: {}", storage_dir.display());
+                info!("Allowed Storage data directory: {}", storage_dir.display());
             }
 
             if let Ok(app_local_data_dir) = app.path().app_local_data_dir() {
                 let storage_local_dir = app_local_data_dir.join("storage_data");
                 fs.allow_directory(&storage_local_dir, true)
                     .expect("Failed to allow Storage local data directory");
-                println!(
+                info!(
                     "Allowed Storage local data directory: {}",
                     storage_local_dir.display()
                 );
@@ -32,11 +64,42 @@ pub fn run() {
             // Initialize the storage node on app startup
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                if let Err(e) =
-                    crate::features::connection::get_storage_manager_with_handle(Some(app_handle))
-                        .await
+                if let Err(e) = crate::features::connection::get_storage_manager_with_handle(Some(
+                    app_handle.clone(),
+                ))
+                .await
                 {
-                    eprintln!("Failed to initialize storage manager: {}", e);
+                    error!("Failed to initialize storage manager: {}", e);
+                }
+
+                let settings = crate::features::settings::load_settings(&app_handle);
+                if settings.gateway.enabled {
+                    if let Err(e) = crate::features::gateway::start_gateway_server(
+                        settings.gateway.port,
+                        app_handle.clone(),
+                    )
+                    .await
+                    {
+                        error!("Failed to start gateway: {}", e);
+                    }
+                }
+                if settings.metrics.enabled {
+                    if let Err(e) = crate::features::metrics::start_metrics_endpoint(
+                        settings.metrics.port,
+                        app_handle.clone(),
+                    )
+                    .await
+                    {
+                        error!("Failed to start metrics endpoint: {}", e);
+                    }
+                }
+
+                crate::features::watch::restore_watch_folders(app_handle.clone()).await;
+                crate::features::mirror::restore_subscriptions(app_handle.clone()).await;
+                crate::features::metrics::start_network_sampler(app_handle.clone()).await;
+                crate::features::peers::start_ban_enforcement(app_handle.clone()).await;
+                if let Err(e) = crate::features::lan::apply_lan_mode(app_handle).await {
+                    error!("Failed to start LAN discovery: {}", e);
                 }
             });
 
@@ -46,11 +109,71 @@ pub fn run() {
             features::connection::get_node_status,
             features::upload::upload_file_to_storage,
             features::download::download_file_from_storage,
+            features::download::download_shared_file,
+            features::download::download_range,
+            features::download::get_cid_info,
             features::connection::connect_to_peer,
             features::connection::get_node_info,
             features::connection::start_node,
-            features::connection::stop_node
+            features::connection::stop_node,
+            features::connection::resolve_port_conflicts,
+            features::connection::pause_transfers,
+            features::connection::get_transfers_paused,
+            features::settings::get_settings,
+            features::settings::update_settings,
+            features::gateway::start_gateway,
+            features::gateway::stop_gateway,
+            features::gateway::gateway_status,
+            features::watch::add_watch_folder,
+            features::watch::remove_watch_folder,
+            features::watch::list_watch_folders,
+            features::mirror::publish_directory,
+            features::mirror::mirror_directory,
+            features::mirror::subscribe_directory,
+            features::mirror::unsubscribe_directory,
+            features::mirror::list_mirror_subscriptions,
+            features::naming::get_naming_key,
+            features::naming::publish_name,
+            features::naming::resolve_name,
+            features::profiles::list_profiles,
+            features::profiles::get_active_profile,
+            features::profiles::create_profile,
+            features::profiles::update_profile,
+            features::profiles::delete_profile,
+            features::profiles::switch_profile,
+            features::identity::export_identity,
+            features::identity::import_identity,
+            features::maintenance::verify_repository,
+            features::maintenance::remove_datasets,
+            features::maintenance::reset_repository,
+            features::maintenance::migrate_repository,
+            features::logging::get_recent_logs,
+            features::logging::set_log_level,
+            features::diagnostics::export_diagnostics,
+            features::metrics::get_network_stats,
+            features::metrics::start_metrics,
+            features::metrics::stop_metrics,
+            features::metrics::metrics_status,
+            features::peers::list_connected_peers,
+            features::peers::disconnect_peer,
+            features::peers::ban_peer,
+            features::peers::unban_peer,
+            features::peers::list_banned_peers,
+            features::bootstrap::list_bootstrap_presets,
+            features::bootstrap::validate_bootstrap_spr,
+            features::bootstrap::add_bootstrap_node,
+            features::bootstrap::remove_bootstrap_node,
+            features::bootstrap::set_bootstrap_preset,
+            features::bootstrap::get_bootstrap_report,
+            features::lan::set_lan_mode,
+            features::lan::get_lan_peers,
+            features::reachability::check_reachability,
+            features::instance::get_repo_lock_status,
+            features::instance::clear_stale_repo_lock,
+            features::shutdown::confirm_exit
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(features::shutdown::handle_window_event)
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| features::shutdown::handle_run_event(app_handle, &event));
 }
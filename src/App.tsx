@@ -1,4 +1,5 @@
 import { useStore } from "@nanostores/react";
+import { useEffect } from "react";
 import {
 	Tabs,
 	TabsContent,
@@ -19,11 +20,19 @@ import { getConnectionStatusText } from "./features/connection/connectionUtils";
 import NodeTab from "./features/node/components/NodeTab";
 import AddPeerDialog from "./features/peers/components/AddPeerDialog";
 import PeersTab from "./features/peers/components/PeersTab";
+import { listenForExitRequests } from "./features/shutdown/shutdownService";
 
 function App() {
 	const connectionStatus = useStore($connectionStatus);
 	const isConnected = useStore($isConnected);
 
+	useEffect(() => {
+		const unlisten = listenForExitRequests();
+		return () => {
+			unlisten.then((stop) => stop());
+		};
+	}, []);
+
 	return (
 		<div className="size-full flex flex-col bg-lsd-surface-primary pt-[env(safe-area-inset-top)] pb-[env(safe-area-inset-bottom)]">
 			<header className="flex p-6 justify-between items-center">
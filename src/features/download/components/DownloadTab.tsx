@@ -11,6 +11,14 @@ interface DownloadResult {
   filepath?: string;
 }
 
+interface CidInfo {
+  cid: string;
+  filename: string | null;
+  mimetype: string | null;
+  dataset_size: number;
+  block_size: number;
+}
+
 export default function DownloadTab() {
   const [cid, setCid] = useState('');
   const [isDownloading, setIsDownloading] = useState(false);
@@ -40,13 +48,16 @@ export default function DownloadTab() {
     }
 
     try {
-      // First, let user select save location
+      // Fetch the manifest so the save dialog can use the original filename
+      const cidInfo = await invoke<CidInfo>('get_cid_info', { cid: cid });
+
+      // Then let user select save location
       const savePath = await save({
         filters: [{
           name: 'All Files',
           extensions: ['*']
         }],
-        defaultPath: `${cid}.bin`
+        defaultPath: cidInfo.filename ?? `${cid}.bin`
       });
 
       if (!savePath) {
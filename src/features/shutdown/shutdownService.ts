@@ -0,0 +1,28 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { ask } from "@tauri-apps/plugin-dialog";
+
+interface ExitRequest {
+	active_transfers: number;
+	timeout_secs: number;
+}
+
+// Asks the user to confirm exiting while transfers are running
+export function listenForExitRequests() {
+	return listen<ExitRequest>("exit-requested", async (event) => {
+		const { active_transfers: transfers, timeout_secs: timeout } = event.payload;
+		const confirmed = await ask(
+			`${transfers} transfer(s) are still running. Exit once they have finished? ` +
+				`Transfers still running after ${timeout} seconds are cancelled.`,
+			{
+				title: "Transfers running",
+				kind: "warning",
+				okLabel: "Exit after transfers",
+				cancelLabel: "Keep running",
+			},
+		);
+		if (confirmed) {
+			await invoke("confirm_exit", { waitForTransfers: true });
+		}
+	});
+}